@@ -0,0 +1,23 @@
+// All registered algorithms of a day against each other on large generated inputs,
+// run with `cargo bench --bench algorithms`
+mod common;
+
+use aocmaxnoe2021::{algorithms, generate};
+
+fn main() {
+    for (day, size) in [(4, 5000), (5, 1000), (7, 10000), (15, 300), (18, 100)] {
+        let input = generate(day, Some(size), 1).unwrap();
+        let (runs, _) = common::best_of(|| algorithms::compare(day, &input).unwrap());
+        assert!(algorithms::agree(&runs), "day {} algorithms disagree: {:?}", day, runs);
+
+        println!("day{} (size {})", day, size);
+        for run in &runs {
+            println!(
+                "  {:<14} {:>10.2?}  speed-up: {:.2}",
+                run.name,
+                run.time,
+                runs[0].time.as_secs_f64() / run.time.as_secs_f64()
+            );
+        }
+    }
+}
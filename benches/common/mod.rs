@@ -0,0 +1,18 @@
+// Timing helpers shared by the benchmarks
+use std::time::{Duration, Instant};
+
+const N_RUNS: usize = 5;
+
+/// Result and fastest run time of f over a few runs
+pub fn best_of<R>(f: impl Fn() -> R) -> (R, Duration) {
+    let mut best = Duration::MAX;
+    let mut result = None;
+
+    for _ in 0..N_RUNS {
+        let t0 = Instant::now();
+        result = Some(f());
+        best = best.min(t0.elapsed());
+    }
+
+    (result.unwrap(), best)
+}
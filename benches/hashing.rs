@@ -0,0 +1,48 @@
+// The in-crate FxHash against the default SipHash on large generated inputs,
+// run with `cargo bench --bench hashing`
+mod common;
+
+use aocmaxnoe2021::fxhash::FxBuildHasher;
+use aocmaxnoe2021::{day13, day14, day5, generate};
+use std::collections::hash_map::RandomState;
+use std::fmt::Debug;
+
+fn compare<R: PartialEq + Debug>(name: &str, sip: impl Fn() -> R, fx: impl Fn() -> R) {
+    let (sip_result, t_sip) = common::best_of(sip);
+    let (fx_result, t_fx) = common::best_of(fx);
+    assert_eq!(sip_result, fx_result, "{} gives different answers", name);
+
+    println!(
+        "{:<12} SipHash: {:>10.2?}  FxHash: {:>10.2?}  speed-up: {:.2}",
+        name,
+        t_sip,
+        t_fx,
+        t_sip.as_secs_f64() / t_fx.as_secs_f64()
+    );
+}
+
+fn main() {
+    let input = generate(5, Some(2000), 1).unwrap();
+    let lines = day5::parse(&input).unwrap();
+    compare(
+        "day5 part2",
+        || day5::count_overlaps::<RandomState>(&lines, true).unwrap(),
+        || day5::count_overlaps::<FxBuildHasher>(&lines, true).unwrap(),
+    );
+
+    let input = generate(13, Some(20000), 1).unwrap();
+    let manual = day13::parse(&input).unwrap();
+    compare(
+        "day13 part2",
+        || day13::render_grid(&day13::fold_all::<RandomState>(&manual)),
+        || day13::render_grid(&day13::fold_all::<FxBuildHasher>(&manual)),
+    );
+
+    let input = generate(14, Some(26), 1).unwrap();
+    let polymer = day14::parse(&input).unwrap();
+    compare(
+        "day14 part2",
+        || day14::polymer_development_with::<RandomState>(&polymer, 40),
+        || day14::polymer_development_with::<FxBuildHasher>(&polymer, 40),
+    );
+}
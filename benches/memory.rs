@@ -0,0 +1,58 @@
+// Time and allocations of the parse and both parts of every day on large generated inputs,
+// run with `cargo bench --bench memory --features count-allocations`
+mod common;
+
+use aocmaxnoe2021::memory::{self, Stats};
+use aocmaxnoe2021::*;
+use std::time::Duration;
+
+fn report(label: &str, time: Duration, stats: Option<Stats>) {
+    match stats {
+        Some(stats) => println!("  {:<6} {:>10.2?}  {}", label, time, stats),
+        None => println!("  {:<6} {:>10.2?}", label, time),
+    }
+}
+
+macro_rules! bench_day {
+    ($day:ident, $number:expr, $size:expr) => {{
+        println!("{} (size {})", stringify!($day), $size);
+        let input = generate($number, Some($size), 1).unwrap();
+
+        let (parsed, stats) = memory::measure(|| $day::parse(&input).unwrap());
+        let (_, time) = common::best_of(|| $day::parse(&input).unwrap());
+        report("parse", time, stats);
+
+        let (_, stats) = memory::measure(|| $day::part1(&parsed));
+        let (_, time) = common::best_of(|| $day::part1(&parsed));
+        report("part1", time, stats);
+
+        let (_, stats) = memory::measure(|| $day::part2(&parsed));
+        let (_, time) = common::best_of(|| $day::part2(&parsed));
+        report("part2", time, stats);
+    }};
+}
+
+fn main() {
+    if !cfg!(feature = "count-allocations") {
+        println!("Allocations are only counted with --features count-allocations\n");
+    }
+
+    bench_day!(day1, 1, 100_000);
+    bench_day!(day2, 2, 100_000);
+    bench_day!(day3, 3, 10_000);
+    bench_day!(day4, 4, 1000);
+    bench_day!(day5, 5, 1000);
+    bench_day!(day6, 6, 10_000);
+    bench_day!(day7, 7, 10_000);
+    bench_day!(day8, 8, 10_000);
+    bench_day!(day9, 9, 500);
+    bench_day!(day10, 10, 10_000);
+    bench_day!(day11, 11, 10);
+    bench_day!(day12, 12, 30);
+    bench_day!(day13, 13, 10_000);
+    bench_day!(day14, 14, 26);
+    bench_day!(day15, 15, 200);
+    bench_day!(day16, 16, 500);
+    bench_day!(day17, 17, 200);
+    bench_day!(day18, 18, 100);
+}
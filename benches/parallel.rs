@@ -0,0 +1,36 @@
+// Single threaded against parallel solutions on large generated inputs,
+// run with `cargo bench --bench parallel`, AOC_THREADS sets the number of threads
+mod common;
+
+use aocmaxnoe2021::{day12, day17, day18, generate, parallel};
+use std::fmt::Debug;
+
+fn compare<R: PartialEq + Debug>(name: &str, f: impl Fn(usize) -> R) {
+    let n_threads = parallel::n_threads();
+    let (single, t_single) = common::best_of(|| f(1));
+    let (multi, t_multi) = common::best_of(|| f(n_threads));
+    assert_eq!(single, multi, "{} gives different answers", name);
+
+    println!(
+        "{:<12} 1 thread: {:>10.2?}  {} threads: {:>10.2?}  speed-up: {:.2}",
+        name,
+        t_single,
+        n_threads,
+        t_multi,
+        t_single.as_secs_f64() / t_multi.as_secs_f64()
+    );
+}
+
+fn main() {
+    let input = generate(12, Some(45), 1).unwrap();
+    let caves = day12::parse(&input).unwrap();
+    compare("day12 part2", |n_threads| day12::count_paths(&caves, false, n_threads));
+
+    let input = generate(17, Some(400), 1).unwrap();
+    let target_area = day17::parse(&input).unwrap();
+    compare("day17", |n_threads| target_area.possible_shots_with(n_threads));
+
+    let input = generate(18, Some(200), 1).unwrap();
+    let numbers = day18::parse(&input).unwrap();
+    compare("day18 part2", |n_threads| day18::largest_magnitude(&numbers, n_threads));
+}
@@ -0,0 +1,19 @@
+// Generate the C header for the exported functions into OUT_DIR, with
+// AOC2021_UPDATE_HEADER set also into the checked in include/aoc2021.h
+use std::env;
+
+fn main() {
+    let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir)).expect("Reading cbindgen.toml failed");
+
+    let bindings = cbindgen::generate_with_config(&crate_dir, config).expect("Generating the C header failed");
+    bindings.write_to_file(format!("{}/aoc2021.h", out_dir));
+    if env::var_os("AOC2021_UPDATE_HEADER").is_some() {
+        bindings.write_to_file(format!("{}/include/aoc2021.h", crate_dir));
+    }
+
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed=AOC2021_UPDATE_HEADER");
+}
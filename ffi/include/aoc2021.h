@@ -0,0 +1,70 @@
+#ifndef AOC2021_H
+#define AOC2021_H
+
+/* Generated by cbindgen from ffi/src/lib.rs, do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Outcome of aoc_solve
+ */
+typedef enum AocStatus {
+  AOC_STATUS_OK = 0,
+  /**
+   * There is no solution for this year, day or part
+   */
+  AOC_STATUS_UNKNOWN_PUZZLE = 1,
+  /**
+   * The input could not be parsed or is not valid utf-8
+   */
+  AOC_STATUS_INVALID_INPUT = 2,
+  /**
+   * The answer does not fit into the integers used by the solution
+   */
+  AOC_STATUS_OVERFLOW = 3,
+  /**
+   * The solution failed unexpectedly
+   */
+  AOC_STATUS_PANIC = 4,
+  /**
+   * Reading or writing a file failed
+   */
+  AOC_STATUS_IO = 5,
+} AocStatus;
+
+/**
+ * The status and, if not null, the answer or an error message
+ */
+typedef struct AocAnswer {
+  enum AocStatus status;
+  char *text;
+} AocAnswer;
+
+/**
+ * Solve one part of a puzzle, input_ptr points to input_len bytes of the puzzle input.
+ * The text of the result must be released with aoc_free.
+ *
+ * # Safety
+ *
+ * input_ptr must be valid for reads of input_len bytes, it may be null if input_len is 0.
+ */
+struct AocAnswer aoc_solve(uint16_t year,
+                           uint8_t day,
+                           uint8_t part,
+                           const uint8_t *input_ptr,
+                           size_t input_len);
+
+/**
+ * Release the text of an answer returned by aoc_solve, null is ignored
+ *
+ * # Safety
+ *
+ * text must be null or the text of an answer that was not released yet.
+ */
+void aoc_free(char *text);
+
+#endif /* AOC2021_H */
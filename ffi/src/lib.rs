@@ -0,0 +1,131 @@
+//! C interface to the solutions, built as a shared library.
+//!
+//! `aoc_solve` returns the answer to one part of a puzzle as a string owned by
+//! the library, which has to be released with `aoc_free`. The header is
+//! generated into `include/aoc2021.h`.
+use aocmaxnoe2021::report::{solve_part, DAYS};
+use aocmaxnoe2021::Error;
+use std::ffi::{c_char, CString};
+use std::panic;
+use std::ptr;
+
+/// Outcome of aoc_solve
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AocStatus {
+    Ok = 0,
+    /// There is no solution for this year, day or part
+    UnknownPuzzle = 1,
+    /// The input could not be parsed or is not valid utf-8
+    InvalidInput = 2,
+    /// The answer does not fit into the integers used by the solution
+    Overflow = 3,
+    /// The solution failed unexpectedly
+    Panic = 4,
+    /// Reading or writing a file failed
+    Io = 5,
+}
+
+/// The status and, if not null, the answer or an error message
+#[repr(C)]
+#[derive(Debug)]
+pub struct AocAnswer {
+    pub status: AocStatus,
+    pub text: *mut c_char,
+}
+
+fn answer(status: AocStatus, text: &str) -> AocAnswer {
+    // answers and error messages never contain a nul byte, but better safe than sorry
+    let text = CString::new(text.replace('\0', " ")).map_or(ptr::null_mut(), CString::into_raw);
+    AocAnswer { status, text }
+}
+
+/// Solve one part of a puzzle, input_ptr points to input_len bytes of the puzzle input.
+/// The text of the result must be released with aoc_free.
+///
+/// # Safety
+///
+/// input_ptr must be valid for reads of input_len bytes, it may be null if input_len is 0.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_solve(year: u16, day: u8, part: u8, input_ptr: *const u8, input_len: usize) -> AocAnswer {
+    if year != 2021 || !DAYS.contains(&day) || !(1..=2).contains(&part) {
+        return answer(AocStatus::UnknownPuzzle, &format!("no solution for {} day {} part {}", year, day, part));
+    }
+
+    let bytes = match (input_ptr.is_null(), input_len) {
+        (_, 0) => &[][..],
+        (true, _) => return answer(AocStatus::InvalidInput, "the input is null"),
+        (false, _) => std::slice::from_raw_parts(input_ptr, input_len),
+    };
+    let input = match std::str::from_utf8(bytes) {
+        Ok(input) => input,
+        Err(_) => return answer(AocStatus::InvalidInput, "the input is not valid utf-8"),
+    };
+
+    // a panic must not unwind into the caller
+    match panic::catch_unwind(|| solve_part(day, part, input)) {
+        Ok(Ok(text)) => answer(AocStatus::Ok, &text),
+        Ok(Err(err @ Error::Parse(_))) => answer(AocStatus::InvalidInput, &err.to_string()),
+        Ok(Err(err @ Error::Overflow)) => answer(AocStatus::Overflow, &err.to_string()),
+        Ok(Err(err @ Error::Io(_))) => answer(AocStatus::Io, &err.to_string()),
+        Err(_) => answer(AocStatus::Panic, "the solution panicked"),
+    }
+}
+
+/// Release the text of an answer returned by aoc_solve, null is ignored
+///
+/// # Safety
+///
+/// text must be null or the text of an answer that was not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_free(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{aoc_free, aoc_solve, AocStatus};
+    use std::ffi::CStr;
+
+    fn solve(year: u16, day: u8, part: u8, input: &[u8]) -> (AocStatus, String) {
+        unsafe {
+            let answer = aoc_solve(year, day, part, input.as_ptr(), input.len());
+            let text = CStr::from_ptr(answer.text).to_str().unwrap().to_string();
+            aoc_free(answer.text);
+            (answer.status, text)
+        }
+    }
+
+    #[test]
+    fn answers() {
+        let input = b"199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n";
+        assert_eq!(solve(2021, 1, 1, input), (AocStatus::Ok, "7".to_string()));
+        assert_eq!(solve(2021, 1, 2, input), (AocStatus::Ok, "5".to_string()));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(solve(2020, 1, 1, b"1").0, AocStatus::UnknownPuzzle);
+        assert_eq!(solve(2021, 19, 1, b"1").0, AocStatus::UnknownPuzzle);
+        assert_eq!(solve(2021, 1, 3, b"1").0, AocStatus::UnknownPuzzle);
+        assert_eq!(solve(2021, 1, 1, b"one"), (AocStatus::InvalidInput, "Invalid input, line 1: invalid measurement: invalid digit found in string".to_string()));
+        assert_eq!(solve(2021, 1, 1, b"\xff").0, AocStatus::InvalidInput);
+        assert_eq!(solve(2021, 6, 1, b"").0, AocStatus::InvalidInput);
+
+        unsafe {
+            assert_eq!(aoc_solve(2021, 1, 1, std::ptr::null(), 3).status, AocStatus::InvalidInput);
+            aoc_free(std::ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn header_is_current() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/aoc2021.h"));
+        assert!(
+            include_str!("../include/aoc2021.h") == generated,
+            "include/aoc2021.h is outdated, rebuild with AOC2021_UPDATE_HEADER=1"
+        );
+    }
+}
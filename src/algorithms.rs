@@ -0,0 +1,121 @@
+//! Alternative algorithms for days with competing solutions.
+//!
+//! Every such day registers its algorithms under a name in an `ALGORITHMS`
+//! list, the first one is the default. `--algo <name>` selects another one
+//! and `verify <day>` runs all of them and compares their answers and speed.
+use crate::error;
+use crate::input;
+use crate::report::Answer;
+use crate::{day15, day18, day4, day5, day7};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A named way to solve both parts of a day
+pub struct Algorithm<I: ?Sized, R> {
+    pub name: &'static str,
+    pub part1: fn(&I) -> R,
+    pub part2: fn(&I) -> R,
+}
+
+/// Days with alternative algorithms
+pub const DAYS: [u8; 5] = [4, 5, 7, 15, 18];
+
+static CHOICE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Use the algorithm with the given name instead of the default one
+pub fn choose(name: Option<&str>) {
+    *CHOICE.lock().unwrap() = name.map(str::to_string);
+}
+
+/// The chosen algorithm, or the default one if none was chosen
+pub fn select<I: ?Sized, R>(algorithms: &'static [Algorithm<I, R>]) -> error::Result<&'static Algorithm<I, R>> {
+    match CHOICE.lock().unwrap().as_deref() {
+        None => Ok(&algorithms[0]),
+        Some(name) => algorithms.iter().find(|a| a.name == name).ok_or_else(|| {
+            let names: Vec<_> = algorithms.iter().map(|a| a.name).collect();
+            error::Error::Parse(format!("unknown algorithm {:?}, expected one of {}", name, names.join(", ")))
+        }),
+    }
+}
+
+/// The answers of one algorithm and the time it took for both parts
+#[derive(Debug)]
+pub struct Run {
+    pub name: &'static str,
+    pub part1: String,
+    pub part2: String,
+    pub time: Duration,
+}
+
+fn run_all<I: ?Sized, R: Answer>(algorithms: &[Algorithm<I, R>], input: &I) -> Vec<Run> {
+    algorithms
+        .iter()
+        .map(|algorithm| {
+            let t0 = Instant::now();
+            let part1 = (algorithm.part1)(input).text();
+            let part2 = (algorithm.part2)(input).text();
+            Run { name: algorithm.name, part1, part2, time: t0.elapsed() }
+        })
+        .collect()
+}
+
+/// Solve the input with every algorithm of the day, in the order they are registered
+pub fn compare(day: u8, input: &str) -> error::Result<Vec<Run>> {
+    Ok(match day {
+        4 => run_all(day4::ALGORITHMS, &day4::parse(input)?),
+        5 => run_all(day5::ALGORITHMS, &day5::parse(input)?),
+        7 => run_all(day7::ALGORITHMS, &day7::parse(input)?),
+        15 => run_all(day15::ALGORITHMS, &day15::parse(input)?),
+        18 => run_all(day18::ALGORITHMS, &day18::parse(input)?),
+        _ => return Err(error::Error::Parse(format!("day {} has no alternative algorithms", day))),
+    })
+}
+
+/// Whether all algorithms found the same answers
+pub fn agree(runs: &[Run]) -> bool {
+    runs.iter().all(|run| run.part1 == runs[0].part1 && run.part2 == runs[0].part2)
+}
+
+/// Compare all algorithms of a day on its input
+pub fn verify(day: u8) -> error::Result<()> {
+    let input = input::get_input(day);
+    let runs = compare(day, &input)?;
+
+    for run in &runs {
+        println!("{:<14} Part1: {:<16} Part2: {:<16} Time: {} us", run.name, run.part1, run.part2, run.time.as_micros());
+    }
+
+    if !agree(&runs) {
+        println!("The algorithms disagree");
+        std::process::exit(1);
+    }
+    println!("All algorithms agree");
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::generate::generate;
+
+    #[test]
+    fn algorithms_agree() {
+        for day in super::DAYS {
+            for seed in 1..=3 {
+                let input = generate(day, None, seed).unwrap();
+                let runs = super::compare(day, &input).unwrap();
+                assert!(runs.len() > 1);
+                assert!(super::agree(&runs), "day {} seed {}: {:?}", day, seed, runs);
+            }
+        }
+    }
+
+    #[test]
+    fn select() {
+        super::choose(Some("nope"));
+        assert!(super::select(crate::day7::ALGORITHMS).is_err());
+        super::choose(Some("exhaustive"));
+        assert_eq!(super::select(crate::day7::ALGORITHMS).unwrap().name, "exhaustive");
+        super::choose(None);
+        assert_eq!(super::select(crate::day7::ALGORITHMS).unwrap().name, "median-mean");
+    }
+}
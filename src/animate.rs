@@ -0,0 +1,183 @@
+use crate::error;
+use crate::{day11, day13, day6, input};
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+// A single state of a step based simulation that can be drawn to the terminal
+pub trait Frame {
+    fn render(&self) -> String;
+}
+
+enum Control {
+    TogglePause,
+    Step,
+    Quit,
+}
+
+// Terminals are line buffered, so every control is a line on stdin:
+// enter or p toggles pause, s advances a single step, q quits
+fn parse_control(line: &str) -> Option<Control> {
+    match line.trim() {
+        "" | "p" => Some(Control::TogglePause),
+        "s" | "n" => Some(Control::Step),
+        "q" => Some(Control::Quit),
+        _ => None,
+    }
+}
+
+fn spawn_control_reader() -> Receiver<Control> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let control = match line {
+                Ok(line) => parse_control(&line),
+                Err(_) => Some(Control::Quit),
+            };
+            if let Some(control) = control {
+                if sender.send(control).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    receiver
+}
+
+pub struct Animator {
+    frame_time: Duration,
+    paused: bool,
+}
+
+/// How long each of fps frames per second is shown, None unless fps is positive and finite
+/// and the frame time can be represented
+pub fn frame_time(fps: f64) -> Option<Duration> {
+    if fps.is_finite() && fps > 0.0 {
+        Duration::try_from_secs_f64(1.0 / fps).ok()
+    } else {
+        None
+    }
+}
+
+impl Animator {
+    pub fn new(frame_time: Duration) -> Self {
+        Self{frame_time, paused: false}
+    }
+
+    pub fn play<F: Frame>(&mut self, frames: impl Iterator<Item = F>) {
+        let controls = spawn_control_reader();
+        let mut stdout = io::stdout();
+
+        for (step, frame) in frames.enumerate() {
+            let status = if self.paused { "paused" } else { "playing" };
+            write!(stdout, "{}{}", CLEAR_SCREEN, frame.render()).unwrap();
+            writeln!(stdout, "\nStep {} [{}]  <enter>/p: pause  s: step  q: quit", step, status).unwrap();
+            stdout.flush().unwrap();
+
+            if !self.wait(&controls) {
+                return;
+            }
+        }
+    }
+
+    // Returns false if the animation should stop
+    fn wait(&mut self, controls: &Receiver<Control>) -> bool {
+        loop {
+            let control = if self.paused {
+                match controls.recv() {
+                    Ok(control) => control,
+                    Err(_) => return false,
+                }
+            } else {
+                match controls.recv_timeout(self.frame_time) {
+                    Ok(control) => control,
+                    Err(RecvTimeoutError::Timeout) => return true,
+                    // stdin is closed, there is nobody left to control us
+                    Err(RecvTimeoutError::Disconnected) => {
+                        thread::sleep(self.frame_time);
+                        return true;
+                    }
+                }
+            };
+
+            match control {
+                Control::TogglePause => {
+                    self.paused = !self.paused;
+                    if !self.paused {
+                        return true;
+                    }
+                }
+                Control::Step => {
+                    self.paused = true;
+                    return true;
+                }
+                Control::Quit => return false,
+            }
+        }
+    }
+}
+
+pub fn animate(day: u8, frame_time: Duration) -> error::Result<()> {
+    let mut animator = Animator::new(frame_time);
+    let input = input::get_input(day);
+
+    match day {
+        6 => animator.play(day6::snapshots(&day6::parse(&input)?).take(257)),
+        11 => animator.play(day11::snapshots(&day11::parse(&input)?)),
+        13 => animator.play(day13::snapshots(&day13::parse(&input)?)),
+        _ => {
+            println!("No animation for day {}", day);
+            std::process::exit(1);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Animator, Control};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn fps() {
+        assert_eq!(super::frame_time(10.0), Some(Duration::from_millis(100)));
+        for fps in [0.0, -1.0, f64::NAN, f64::INFINITY, 1e-300] {
+            assert_eq!(super::frame_time(fps), None, "{}", fps);
+        }
+    }
+
+    #[test]
+    fn controls() {
+        assert!(matches!(super::parse_control("\n"), Some(Control::TogglePause)));
+        assert!(matches!(super::parse_control(" s "), Some(Control::Step)));
+        assert!(matches!(super::parse_control("q"), Some(Control::Quit)));
+        assert!(super::parse_control("x").is_none());
+
+        let mut animator = Animator::new(Duration::from_millis(1));
+        let (sender, receiver) = mpsc::channel();
+
+        // without input the next frame follows after the frame time
+        assert!(animator.wait(&receiver));
+
+        sender.send(Control::Step).unwrap();
+        assert!(animator.wait(&receiver));
+        assert!(animator.paused);
+
+        sender.send(Control::TogglePause).unwrap();
+        assert!(animator.wait(&receiver));
+        assert!(!animator.paused);
+
+        sender.send(Control::Quit).unwrap();
+        assert!(!animator.wait(&receiver));
+
+        // a closed stdin keeps playing, but ends a paused animation
+        drop(sender);
+        assert!(animator.wait(&receiver));
+        animator.paused = true;
+        assert!(!animator.wait(&receiver));
+    }
+}
@@ -0,0 +1,72 @@
+// Run the fuzz entry point offline: on the committed fuzz corpus, on generated
+// inputs and on deterministic random mutations of both
+use crate::fuzz;
+use crate::generate::generate;
+use crate::rng::Rng;
+use std::fs;
+use std::panic;
+use std::path::Path;
+
+const N_MUTATIONS: usize = 1000;
+
+// bytes that are meaningful to at least one of the parsers
+const INTERESTING: &[u8] = b"0123456789ABCDEFabcdefgxy,-|=.[]<>(){} \n\xff";
+
+fn corpus(day: u8) -> Vec<Vec<u8>> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus").join(format!("day{}", day));
+    let mut inputs: Vec<Vec<u8>> = match fs::read_dir(dir) {
+        Ok(entries) => entries.map(|entry| fs::read(entry.unwrap().path()).unwrap()).collect(),
+        Err(_) => Vec::new(),
+    };
+    // the directory order is not deterministic
+    inputs.sort();
+
+    for seed in 1..=3 {
+        inputs.extend(generate(day, Some(5), seed).map(String::into_bytes));
+    }
+    inputs
+}
+
+fn mutate(rng: &mut Rng, input: &[u8]) -> Vec<u8> {
+    let mut data = input.to_vec();
+
+    for _ in 0..=rng.below(4) {
+        let pos = rng.below(data.len() as u64 + 1) as usize;
+        let end = (pos + rng.below(16) as usize).min(data.len());
+
+        match rng.below(5) {
+            0 => data.truncate(pos),
+            1 => drop(data.drain(pos..end)),
+            2 => data.insert(pos, *rng.choose(INTERESTING)),
+            3 if pos < data.len() => data[pos] = *rng.choose(INTERESTING),
+            _ => {
+                let chunk = data[pos..end].to_vec();
+                data.splice(pos..pos, chunk);
+            }
+        }
+    }
+    data
+}
+
+fn assert_no_panic(day: u8, data: &[u8]) {
+    if panic::catch_unwind(|| fuzz::parse(day, data)).is_err() {
+        panic!("Parser of day {} panicked on {:?}", day, String::from_utf8_lossy(data));
+    }
+}
+
+#[test]
+fn fuzz_corpus() {
+    for day in fuzz::DAYS {
+        let mut rng = Rng::new(day as u64);
+        let corpus = corpus(day);
+
+        for input in &corpus {
+            assert_no_panic(day, input);
+        }
+
+        for _ in 0..N_MUTATIONS {
+            let input = rng.choose(&corpus);
+            assert_no_panic(day, &mutate(&mut rng, input));
+        }
+    }
+}
@@ -0,0 +1,252 @@
+// Compare the solutions that rely on shortcuts against naive reference
+// implementations on lots of small, randomly generated inputs
+use crate::rng::Rng;
+use crate::fxhash::FxBuildHasher;
+use crate::{day14, day17, day3, day5, day6, day7};
+use itertools::Itertools;
+use std::collections::HashMap;
+
+const N_CASES: usize = 2000;
+
+fn random_crabs(rng: &mut Rng) -> String {
+    let n = rng.range(1..=30);
+    let max_position = *rng.choose(&[5, 50, 2000]);
+    (0..n).map(|_| rng.range(0..=max_position)).join(",")
+}
+
+fn reference_crabs(positions: &[i64], fuel: impl Fn(i64) -> i64) -> u64 {
+    let min = *positions.iter().min().unwrap();
+    let max = *positions.iter().max().unwrap();
+
+    (min..=max)
+        .map(|target| positions.iter().map(|p| fuel((p - target).abs())).sum::<i64>())
+        .min()
+        .unwrap() as u64
+}
+
+#[test]
+fn day7_alignment() {
+    let mut rng = Rng::new(7);
+
+    for _ in 0..N_CASES {
+        let input = random_crabs(&mut rng);
+        let positions = day7::parse(&input).unwrap();
+
+        assert_eq!(day7::part1(&positions), Ok(reference_crabs(&positions, |steps| steps)), "{}", input);
+        assert_eq!(
+            day7::part2(&positions),
+            Ok(reference_crabs(&positions, |steps| steps * (steps + 1) / 2)),
+            "{}",
+            input
+        );
+    }
+}
+
+fn random_polymer(rng: &mut Rng) -> String {
+    let elements = &['B', 'C', 'H', 'N', 'O'][..rng.range(1..=5) as usize];
+    let template: String = (0..rng.range(2..=8)).map(|_| *rng.choose(elements)).collect();
+
+    let rules = elements
+        .iter()
+        .cartesian_product(elements)
+        .map(|(a, b)| format!("{}{} -> {}", a, b, rng.choose(elements)))
+        .join("\n");
+
+    format!("{}\n\n{}\n", template, rules)
+}
+
+fn reference_polymer(input: &str, n_steps: u16) -> u128 {
+    let (template, rules) = input.split_once("\n\n").unwrap();
+    let rules: HashMap<(char, char), char> = rules
+        .lines()
+        .map(|l| {
+            let (pair, insert) = l.split_once(" -> ").unwrap();
+            (pair.chars().collect_tuple().unwrap(), insert.chars().next().unwrap())
+        })
+        .collect();
+
+    let mut polymer: Vec<char> = template.chars().collect();
+    for _ in 0..n_steps {
+        let mut next = vec![polymer[0]];
+        for (a, b) in polymer.iter().copied().tuple_windows() {
+            next.push(rules[&(a, b)]);
+            next.push(b);
+        }
+        polymer = next;
+    }
+
+    let counts = polymer.iter().counts();
+    (*counts.values().max().unwrap() - *counts.values().min().unwrap()) as u128
+}
+
+#[test]
+fn day14_pair_counting() {
+    let mut rng = Rng::new(14);
+
+    for _ in 0..N_CASES {
+        let input = random_polymer(&mut rng);
+        let n_steps = rng.range(0..=10) as u16;
+        let polymer = day14::parse(&input).unwrap();
+
+        assert_eq!(
+            day14::polymer_development(&polymer, n_steps),
+            Ok(reference_polymer(&input, n_steps)),
+            "{} steps\n{}",
+            n_steps,
+            input
+        );
+    }
+}
+
+fn reference_fishes(timers: &[u8], days: usize) -> usize {
+    let mut timers = timers.to_vec();
+    for _ in 0..days {
+        let n_new = timers.iter().filter(|t| **t == 0).count();
+        for timer in timers.iter_mut() {
+            *timer = if *timer == 0 { 6 } else { *timer - 1 };
+        }
+        timers.extend(std::iter::repeat_n(8, n_new));
+    }
+    timers.len()
+}
+
+#[test]
+fn day6_lanternfish() {
+    let mut rng = Rng::new(6);
+
+    for _ in 0..N_CASES {
+        let timers: Vec<u8> = (0..rng.range(1..=20)).map(|_| rng.range(0..=8) as u8).collect();
+        let days = rng.range(0..=60) as usize;
+        let input = timers.iter().join(",");
+
+        assert_eq!(
+            day6::simulate_fishes(&day6::parse(&input).unwrap(), days),
+            Ok(reference_fishes(&timers, days) as u128),
+            "{} days\n{}",
+            days,
+            input
+        );
+    }
+}
+
+fn random_target_area(rng: &mut Rng) -> String {
+    let min_x = rng.range(1..=40);
+    let max_x = min_x + rng.range(0..=20);
+    let max_y = rng.range(-40..=-1);
+    let min_y = max_y - rng.range(0..=20);
+    format!("target area: x={}..{}, y={}..{}\n", min_x, max_x, min_y, max_y)
+}
+
+fn reference_trick_shots(input: &str) -> (i64, usize) {
+    let (min_x, max_x, min_y, max_y) = input
+        .trim()
+        .trim_start_matches("target area: x=")
+        .split(&['.', ',', '=', ' ', 'y'][..])
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<i32>().unwrap())
+        .collect_tuple()
+        .unwrap();
+
+    let mut best_height = 0;
+    let mut n_shots = 0;
+
+    // faster shots step over the area, upward ones when they fall back past y = 0
+    let bound = [min_x, max_x, min_y, max_y].iter().map(|c| c.abs()).max().unwrap() + 1;
+    for vx0 in -bound..=bound {
+        for vy0 in -bound..=bound {
+            let (mut x, mut y, mut vx, mut vy) = (0, 0, vx0, vy0);
+            let mut height = 0;
+
+            // simulate until the probe is past or falling below the target area
+            while (vy >= 0 || y >= min_y) && (vx < 0 || x <= max_x) {
+                x += vx;
+                y += vy;
+                vx -= vx.signum();
+                vy -= 1;
+                height = height.max(y);
+
+                if x >= min_x && x <= max_x && y >= min_y && y <= max_y {
+                    n_shots += 1;
+                    best_height = best_height.max(height);
+                    break;
+                }
+            }
+        }
+    }
+
+    (best_height as i64, n_shots)
+}
+
+#[test]
+fn day17_velocity_bounds() {
+    let mut rng = Rng::new(17);
+
+    for _ in 0..N_CASES {
+        let input = random_target_area(&mut rng);
+        let target_area = day17::parse(&input).unwrap();
+
+        assert_eq!(target_area.possible_shots(), reference_trick_shots(&input), "{}", input);
+    }
+}
+
+// Short lines in a small area around the origin, so that lines often overlap along a stretch or in single points
+fn random_vents(rng: &mut Rng) -> String {
+    (0..rng.range(1..=20))
+        .map(|_| {
+            let (x0, y0) = (rng.range(-5..=4), rng.range(-5..=4));
+            let length = rng.range(0..=9);
+            let (dx, dy) = *rng.choose(&[(1, 0), (0, 1), (1, 1), (1, -1), (-1, 0), (0, -1), (-1, -1), (-1, 1)]);
+            let (x1, y1) = ((x0 + dx * length).clamp(-5, 4), (y0 + dy * length).clamp(-5, 4));
+            // clamping may bend a diagonal, shorten it to the step it can take in both directions
+            let n = (x1 - x0).abs().min((y1 - y0).abs());
+            let (x1, y1) = if dx != 0 && dy != 0 { (x0 + dx * n, y0 + dy * n) } else { (x1, y1) };
+            format!("{},{} -> {},{}", x0, y0, x1, y1)
+        })
+        .join("\n")
+}
+
+#[test]
+fn day5_analytic_overlaps() {
+    let mut rng = Rng::new(5);
+
+    for _ in 0..N_CASES {
+        let input = random_vents(&mut rng);
+        let lines = day5::parse(&input).unwrap();
+
+        for with_diagonals in [false, true] {
+            assert_eq!(
+                day5::count_overlaps_analytic(&lines, with_diagonals),
+                day5::count_overlaps::<FxBuildHasher>(&lines, with_diagonals).unwrap() as u128,
+                "{}",
+                input
+            );
+        }
+    }
+}
+
+// Filter the numbers bit by bit as described in the puzzle, None if no single number is left
+fn reference_rating(n_bits: usize, numbers: &[u64], most_common: bool) -> Option<u64> {
+    let mut numbers = numbers.to_vec();
+    for bit in (0..n_bits).rev() {
+        if numbers.len() == 1 {
+            break;
+        }
+        let n_ones = numbers.iter().filter(|n| *n & (1 << bit) != 0).count();
+        let keep_ones = (2 * n_ones >= numbers.len()) == most_common;
+        numbers.retain(|n| (*n & (1 << bit) != 0) == keep_ones);
+    }
+    (numbers.len() == 1).then(|| numbers[0])
+}
+
+#[test]
+fn day3_ratings() {
+    let mut rng = Rng::new(3);
+
+    for _ in 0..N_CASES {
+        let n_bits = rng.range(1..=8) as usize;
+        let numbers: Vec<u64> = (0..rng.range(1..=40)).map(|_| rng.below(1 << n_bits)).collect();
+
+        let expected = reference_rating(n_bits, &numbers, true).zip(reference_rating(n_bits, &numbers, false));
+        assert_eq!(day3::ratings(n_bits, &numbers, day3::OXYGEN, day3::CO2).ok(), expected, "{:?}", numbers);
+    }
+}
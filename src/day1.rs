@@ -1,51 +1,213 @@
+//! Day 1: Sonar Sweep
+use crate::error::{self, ensure, OrInvalid};
 use crate::input;
+use crate::memory;
+use std::collections::VecDeque;
+use std::io::BufRead;
 
-fn parse_input(input: &String) -> Vec<i32> {
-    input
-        .trim()
-        .lines()
-        .map(str::parse::<i32>)
-        .map(Result::unwrap)
-        .collect()
+/// Parse the depth measurements, one per line
+pub fn parse(input: &str) -> error::Result<Vec<i32>> {
+    input::parse_lines(input, parse_measurement)
 }
 
-fn part1(numbers: &Vec<i32>) -> i32 {
-    let mut n_larger = 0;
-    let mut iter = numbers.iter();
-    let mut before = iter.next().expect("Expected at least one number");
+fn parse_measurement(line: &str) -> error::Result<i32> {
+    line.parse().or_invalid("invalid measurement")
+}
+
+/// How a window has to differ from the one before to be counted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Increase,
+    Decrease,
+    Plateau,
+}
 
-    for number in iter {
-        if number > before {
-            n_larger += 1;
+impl Change {
+    fn matches(self, previous: i64, current: i64) -> bool {
+        match self {
+            Change::Increase => current > previous,
+            Change::Decrease => current < previous,
+            Change::Plateau => current == previous,
         }
-        before = number;
     }
+}
 
-    n_larger
+/// How the measurements in a window are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Sum,
+    /// All windows have the same size, so their means compare like their sums
+    Mean,
+    Min,
+    Max,
 }
 
-fn part2(numbers: &Vec<i32>) -> i32 {
-    let mut n_larger = 0;
-    let mut current_sum: i32;
-    let window = 3;
+/// Compares each window of consecutive measurements with the one before,
+/// measurements are pushed one at a time, so any iterator or stream can be analyzed
+#[derive(Debug, Clone)]
+pub struct SlidingWindow {
+    size: usize,
+    change: Change,
+    aggregate: Aggregate,
+    window: VecDeque<i32>,
+    // i64, so the sum of up to 2^32 measurements cannot overflow
+    sum: i64,
+    previous: Option<i64>,
+    n_windows: usize,
+    count: usize,
+}
 
-    let mut previous_sum: i32 = numbers[0..window].iter().sum();
+impl SlidingWindow {
+    pub fn new(size: usize, change: Change, aggregate: Aggregate) -> error::Result<Self> {
+        ensure(size > 0, "the window size must be at least 1")?;
+        Ok(Self {
+            size,
+            change,
+            aggregate,
+            window: VecDeque::with_capacity(size + 1),
+            sum: 0,
+            previous: None,
+            n_windows: 0,
+            count: 0,
+        })
+    }
 
-    for i in 1..=(numbers.len() - window) {
-        current_sum = numbers[i..i + window].iter().sum();
+    fn value(&self) -> i64 {
+        match self.aggregate {
+            Aggregate::Sum | Aggregate::Mean => self.sum,
+            Aggregate::Min => *self.window.iter().min().unwrap() as i64,
+            Aggregate::Max => *self.window.iter().max().unwrap() as i64,
+        }
+    }
 
-        if current_sum > previous_sum {
-            n_larger += 1;
+    /// Add the next measurement, returns the position of the window ending with it
+    /// if it differs from the window before as wanted. Windows are numbered by their first measurement.
+    pub fn push(&mut self, number: i32) -> Option<usize> {
+        self.window.push_back(number);
+        self.sum += number as i64;
+        if self.window.len() > self.size {
+            self.sum -= self.window.pop_front().unwrap() as i64;
+        }
+        if self.window.len() < self.size {
+            return None;
         }
-        previous_sum = current_sum;
+
+        let value = self.value();
+        let position = self.n_windows;
+        self.n_windows += 1;
+
+        let previous = self.previous.replace(value)?;
+        if !self.change.matches(previous, value) {
+            return None;
+        }
+        self.count += 1;
+        Some(position)
+    }
+
+    /// Number of windows differing as wanted so far
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+/// Result of comparing all consecutive windows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowAnalysis {
+    pub count: usize,
+    /// Positions of the windows that differ as wanted from the one before
+    pub positions: Vec<usize>,
+}
+
+/// Compare every window of size consecutive measurements with the one before
+pub fn analyze_windows(
+    numbers: impl IntoIterator<Item = i32>,
+    size: usize,
+    change: Change,
+    aggregate: Aggregate,
+) -> error::Result<WindowAnalysis> {
+    let mut window = SlidingWindow::new(size, change, aggregate)?;
+    let positions = numbers.into_iter().filter_map(|number| window.push(number)).collect();
+    Ok(WindowAnalysis { count: window.count(), positions })
+}
+
+fn count_increases(numbers: &[i32], size: usize) -> usize {
+    let mut window = SlidingWindow::new(size, Change::Increase, Aggregate::Sum).expect("The window size is positive");
+    for number in numbers {
+        window.push(*number);
     }
+    window.count()
+}
+
+/// Number of measurements larger than the previous one
+pub fn part1(numbers: &[i32]) -> usize {
+    count_increases(numbers, 1)
+}
 
-    n_larger
+/// Number of three-measurement windows with a larger sum than the previous one
+pub fn part2(numbers: &[i32]) -> usize {
+    count_increases(numbers, 3)
 }
 
-pub fn day1() {
-    let input = input::get_input(1);
-    let numbers = parse_input(&input);
-    println!("Part1: {}", part1(&numbers));
-    println!("Part2: {}", part2(&numbers));
+/// Solve both parts in a single pass over the measurements, in constant memory
+pub fn solve_reader(reader: impl BufRead) -> error::Result<(usize, usize)> {
+    let mut single = SlidingWindow::new(1, Change::Increase, Aggregate::Sum)?;
+    let mut triple = SlidingWindow::new(3, Change::Increase, Aggregate::Sum)?;
+
+    input::for_each_line(reader, |line| {
+        let number = parse_measurement(line)?;
+        single.push(number);
+        triple.push(number);
+        Ok(())
+    })?;
+
+    Ok((single.count(), triple.count()))
+}
+
+pub fn day1() -> error::Result<()> {
+    let (part1, part2) = memory::record("both parts", || solve_reader(input::get_input_reader(1)))?;
+    println!("Part1: {}", part1);
+    println!("Part2: {}", part2);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{analyze_windows, Aggregate, Change};
+
+    const EXAMPLE: [i32; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+    #[test]
+    fn parts() {
+        assert_eq!(super::part1(&EXAMPLE), 7);
+        assert_eq!(super::part2(&EXAMPLE), 5);
+        assert_eq!(super::solve_reader(&b"199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n"[..]), Ok((7, 5)));
+    }
+
+    #[test]
+    fn short_input() {
+        assert_eq!(super::part1(&[]), 0);
+        assert_eq!(super::part2(&[1, 2]), 0);
+        assert_eq!(super::part2(&[1, 2, 3]), 0);
+    }
+
+    #[test]
+    fn windows() {
+        let analysis = analyze_windows(EXAMPLE, 1, Change::Increase, Aggregate::Sum).unwrap();
+        assert_eq!(analysis.positions, vec![1, 2, 3, 5, 6, 7, 9]);
+
+        let analysis = analyze_windows(EXAMPLE, 1, Change::Decrease, Aggregate::Sum).unwrap();
+        assert_eq!((analysis.count, analysis.positions), (2, vec![4, 8]));
+
+        // window sums 607 618 618 617 647 716 769 792
+        let analysis = analyze_windows(EXAMPLE, 3, Change::Plateau, Aggregate::Mean).unwrap();
+        assert_eq!(analysis.positions, vec![2]);
+
+        let analysis = analyze_windows(EXAMPLE, 2, Change::Increase, Aggregate::Min).unwrap();
+        assert_eq!(analysis.positions, vec![1, 2, 5, 6, 7]);
+
+        let analysis = analyze_windows(EXAMPLE, 2, Change::Decrease, Aggregate::Max).unwrap();
+        assert_eq!(analysis.positions, vec![4, 8]);
+
+        assert!(analyze_windows(EXAMPLE, 0, Change::Increase, Aggregate::Sum).is_err());
+    }
 }
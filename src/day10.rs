@@ -1,8 +1,13 @@
+//! Day 10: Syntax Scoring
+use crate::error::{self, answer, ensure, OrInvalid, OrOverflow};
 use crate::input;
+use crate::memory;
+use std::io::BufRead;
 use std::time::Instant;
 use phf::{Map, phf_map};
 
-type Input = Vec<String>;
+/// The lines of the navigation subsystem
+pub type Input<'a> = Vec<&'a str>;
 
 
 const CLOSING_PAIR: Map<char, char> = phf_map! {
@@ -13,11 +18,16 @@ const CLOSING_PAIR: Map<char, char> = phf_map! {
 };
 
 
-fn parse_input(input: &String) -> Input {
-    input
-        .lines()
-        .map(String::from)
-        .collect()
+fn check_line(line: &str) -> error::Result<()> {
+    ensure(line.chars().all(|c| "()[]{}<>".contains(c)), "lines may only contain brackets")
+}
+
+/// Parse the lines, only brackets are allowed
+pub fn parse(input: &str) -> error::Result<Input<'_>> {
+    input::parse_lines(input, |line| {
+        check_line(line)?;
+        Ok(line)
+    })
 }
 
 
@@ -33,7 +43,8 @@ fn score(invalid_char: Option<char>) -> i64 {
 }
 
 
-fn find_invalid_char(line: &String) -> Option<char> {
+/// The first closing character that does not match its opening one
+pub fn find_invalid_char(line: &str) -> Option<char> {
     let mut stack: Vec<char> = Vec::new();
     for chr in line.chars() {
         match chr {
@@ -54,7 +65,8 @@ fn find_invalid_char(line: &String) -> Option<char> {
 }
 
 
-fn completion_score(line: &String) -> i64 {
+/// Score of the closing characters needed to complete an incomplete line
+pub fn completion_score(line: &str) -> error::Result<u128> {
     let mut stack: Vec<char> = Vec::new();
 
     for chr in line.chars() {
@@ -72,47 +84,77 @@ fn completion_score(line: &String) -> i64 {
         }
     }
 
-    let mut score = 0;
+    let mut score: u128 = 0;
     stack.reverse();
     for chr in stack {
-        score *= 5;
-        score += match chr {
+        let value = match chr {
             '(' => 1,
             '[' => 2,
             '{' => 3,
             '<' => 4,
             _ => panic!("Unexpected character {}", chr)
         };
+        score = score.checked_mul(5).and_then(|s| s.checked_add(value)).or_overflow()?;
     }
 
-    score
+    Ok(score)
 }
 
 
-fn part1(lines: &Input) -> i64 {
+/// Total syntax error score of the corrupted lines
+pub fn part1(lines: &Input) -> i64 {
     lines.iter()
-        .map(find_invalid_char)
+        .map(|l| find_invalid_char(l))
         .map(score)
         .sum()
 }
 
-fn part2(lines: &Input) -> i64 {
-    let mut scores: Vec<i64> = lines.iter()
+fn median(mut scores: Vec<u128>) -> error::Result<u128> {
+    scores.sort();
+    scores.get(scores.len() / 2).copied().or_invalid("there are no incomplete lines")
+}
+
+/// Median completion score of the incomplete lines
+pub fn part2(lines: &Input) -> error::Result<u128> {
+    let scores: Vec<u128> = lines.iter()
         .filter(|l| find_invalid_char(l).is_none())
-        .map(completion_score)
-        .collect();
+        .map(|l| completion_score(l))
+        .collect::<error::Result<_>>()?;
 
-    scores.sort();
-    scores[scores.len() / 2]
+    median(scores)
 
 }
 
-pub fn day10() {
-    let input = input::get_input(10);
-    let grid = parse_input(&input);
+/// Solve both parts in a single pass over the lines,
+/// only the completion scores of the incomplete lines are kept
+pub fn solve_reader(reader: impl BufRead) -> error::Result<(i64, error::Result<u128>)> {
+    let mut syntax_error_score = 0;
+    let mut scores = Vec::new();
+    let mut overflow = None;
+
+    input::for_each_line(reader, |line| {
+        check_line(line)?;
+        match find_invalid_char(line) {
+            Some(chr) => syntax_error_score += score(Some(chr)),
+            None => match completion_score(line) {
+                Ok(score) => scores.push(score),
+                Err(err) => overflow = Some(err),
+            },
+        }
+        Ok(())
+    })?;
+
+    match overflow {
+        Some(err) => Ok((syntax_error_score, Err(err))),
+        None => Ok((syntax_error_score, median(scores))),
+    }
+}
 
+pub fn day10() -> error::Result<()> {
     let t0 = Instant::now();
-    println!("Part1: {}", part1(&grid));
-    println!("Part2: {}", part2(&grid));
+    let (part1, part2) = memory::record("both parts", || solve_reader(input::get_input_reader(10)))?;
+    println!("Part1: {}", part1);
+    println!("Part2: {}", answer(part2));
     println!("Time: {} us", t0.elapsed().as_micros());
+    Ok(())
 }
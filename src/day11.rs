@@ -1,8 +1,13 @@
+//! Day 11: Dumbo Octopus
+use crate::animate::Frame;
+use crate::error::{self, ensure};
 use crate::input;
+use crate::memory;
 use std::time::Instant;
 use std::collections::VecDeque;
 
-type Input = Vec<Vec<u16>>;
+/// Energy levels of the 10x10 octopus grid
+pub type Input = Vec<Vec<u16>>;
 
 const N_ROWS: usize = 10;
 const N_COLS: usize = 10;
@@ -14,18 +19,28 @@ const NEIGHBORS: [(i32, i32); 8] = [
 ];
 
 
-fn parse_input(input: &String) -> Input {
-    input
-        .lines()
-        .map(|l| l.chars().map(|c| c as u16 - b'0' as u16).collect())
-        .collect()
+/// Parse the energy levels, one row of digits per line
+pub fn parse(input: &str) -> error::Result<Input> {
+    let grid = input::parse_digit_grid(input)?;
+    ensure(grid.len() == N_ROWS && grid[0].len() == N_COLS, "the grid must be 10x10")?;
+
+    Ok(grid.iter().map(|row| row.iter().map(|e| *e as u16).collect()).collect())
+}
+
+
+/// Which octopuses flashed during a step
+pub type Flashes = [[bool; N_COLS]; N_ROWS];
+
+
+/// Advance the grid by one step, returns the number of flashes
+pub fn do_step(grid: &mut Input) -> i64 {
+    do_step_with_flashes(grid, &mut [[false; N_COLS]; N_ROWS])
 }
 
 
-fn do_step(grid: &mut Input) -> i64 {
+pub fn do_step_with_flashes(grid: &mut Input, has_flashed: &mut Flashes) -> i64 {
     let mut n_flashes = 0;
 
-    let mut has_flashed = [[false; N_COLS]; N_ROWS];
     let mut to_check = VecDeque::new();
 
     for row in 0..N_ROWS {
@@ -40,7 +55,7 @@ fn do_step(grid: &mut Input) -> i64 {
         }
     }
 
-    while to_check.len() > 0 {
+    while !to_check.is_empty() {
         let (row, col) = to_check.pop_front().unwrap();
         for (dr, dc) in NEIGHBORS {
             if (row == 0 && dr == -1)
@@ -63,11 +78,9 @@ fn do_step(grid: &mut Input) -> i64 {
         }
     }
 
-    for row in 0..N_ROWS {
-        for col in 0..N_COLS {
-            if grid[row][col] > 9 {
-                grid[row][col] = 0;
-            }
+    for energy in grid.iter_mut().flatten() {
+        if *energy > 9 {
+            *energy = 0;
         }
     }
 
@@ -75,12 +88,53 @@ fn do_step(grid: &mut Input) -> i64 {
 }
 
 
-fn part1(grid: &Input) -> i64 {
+pub struct Snapshot {
+    pub step: usize,
+    pub grid: Input,
+    pub flashes: Flashes,
+}
+
+impl Frame for Snapshot {
+    fn render(&self) -> String {
+        let n_flashes = self.flashes.iter().flatten().filter(|f| **f).count();
+        let mut s = format!("Step {}, {} flashes\n\n", self.step, n_flashes);
+
+        for (row, flashes) in self.grid.iter().zip(self.flashes) {
+            for (energy, flashed) in row.iter().zip(flashes) {
+                if flashed {
+                    s.push_str("\x1b[1;93m0\x1b[0m");
+                } else {
+                    s.push_str(&format!("\x1b[2m{}\x1b[0m", energy));
+                }
+            }
+            s.push('\n');
+        }
+
+        s
+    }
+}
+
+/// The grid after each step, with the octopuses that flashed during that step
+pub fn snapshots(grid: &Input) -> impl Iterator<Item = Snapshot> {
+    let mut grid = grid.clone();
+    let initial = Snapshot{step: 0, grid: grid.clone(), flashes: [[false; N_COLS]; N_ROWS]};
+
+    std::iter::once(initial).chain((1..).map(move |step| {
+        let mut flashes = [[false; N_COLS]; N_ROWS];
+        do_step_with_flashes(&mut grid, &mut flashes);
+        Snapshot{step, grid: grid.clone(), flashes}
+    }))
+}
+
+
+/// Number of flashes during the first 100 steps
+pub fn part1(grid: &Input) -> i64 {
     let mut grid = grid.clone();
     (0..100).map(|_| do_step(&mut grid)).sum()
 }
 
-fn part2(grid: &Input) -> i64 {
+/// First step during which all octopuses flash
+pub fn part2(grid: &Input) -> i64 {
     let mut grid = grid.clone();
     let mut step = 0;
     let mut n_flashes = 0;
@@ -92,12 +146,13 @@ fn part2(grid: &Input) -> i64 {
     step
 }
 
-pub fn day11() {
+pub fn day11() -> error::Result<()> {
     let input = input::get_input(11);
-    let grid = parse_input(&input);
+    let grid = memory::record("parse", || parse(&input))?;
 
     let t0 = Instant::now();
-    println!("Part1: {}", part1(&grid));
-    println!("Part2: {}", part2(&grid));
+    println!("Part1: {}", memory::record("part1", || part1(&grid)));
+    println!("Part2: {}", memory::record("part2", || part2(&grid)));
     println!("Time: {} us", t0.elapsed().as_micros());
+    Ok(())
 }
@@ -1,92 +1,225 @@
+//! Day 12: Passage Pathing
+use crate::error::{self, ensure, OrInvalid};
 use crate::input;
+use crate::memory;
+use crate::parallel;
 use std::time::Instant;
-use std::collections::{HashMap,HashSet};
 use itertools::Itertools;
 
 
-type Input = HashMap<String, HashSet<String>>;
+/// The cave system, caves are identified by their index into names
+pub struct Caves<'a> {
+    pub names: Vec<&'a str>,
+    /// The caves connected to each cave
+    pub connections: Vec<Vec<usize>>,
+    pub small: Vec<bool>,
+    pub start: usize,
+    pub end: usize,
+}
+
+fn cave_id<'a>(names: &mut Vec<&'a str>, name: &'a str) -> error::Result<usize> {
+    match names.iter().position(|n| *n == name) {
+        Some(id) => Ok(id),
+        None => {
+            ensure(!name.is_empty(), "empty cave name")?;
+            names.push(name);
+            Ok(names.len() - 1)
+        }
+    }
+}
 
-fn parse_input(input: &String) -> Input {
-    let mut connections: Input = HashMap::new();
-    input
-        .trim()
-        .lines()
-        .map(|l| l.split("-").map(String::from).collect_tuple().unwrap())
-        .for_each(|(k, v)| {
-            connections.entry(k.clone()).or_insert(HashSet::new()).insert(v.clone());
-            connections.entry(v.clone()).or_insert(HashSet::new()).insert(k.clone());
-        });
+fn connect(connections: &mut [Vec<usize>], a: usize, b: usize) {
+    if !connections[a].contains(&b) {
+        connections[a].push(b);
+    }
+}
 
-    connections
+/// Parse the cave connections, one `a-b` per line
+pub fn parse(input: &str) -> error::Result<Caves<'_>> {
+    let mut names = vec!["start", "end"];
+    let mut connections = vec![Vec::new(); 2];
+
+    let pairs = input::parse_lines(input, |l| l.split('-').collect_tuple().or_invalid("expected a connection a-b"))?;
+    for (a, b) in pairs {
+        let a = cave_id(&mut names, a)?;
+        let b = cave_id(&mut names, b)?;
+        connections.resize(names.len(), Vec::new());
+        connect(&mut connections, a, b);
+        connect(&mut connections, b, a);
+    }
+
+    let small = names.iter().map(|name| is_ascii_lowercase(name)).collect();
+    Ok(Caves{names, connections, small, start: 0, end: 1})
 }
 
 fn is_ascii_lowercase(s: &str) -> bool {
-    return s.is_ascii() && s.to_ascii_lowercase() == s;
+    s.is_ascii() && s.to_ascii_lowercase() == s
 }
 
 
-fn find_paths(start_point: &String, connections: &Input, visited: &mut HashSet<String>) -> usize {
-    let mut n_paths = 0;
+/// The visited caves of a path, a single word as long as there are at most 64 caves
+pub trait CaveSet: Clone + Send + Sync {
+    fn empty(n_caves: usize) -> Self;
+    fn contains(&self, cave: usize) -> bool;
+    fn insert(&mut self, cave: usize);
+}
 
-    if is_ascii_lowercase(&start_point) {
-        visited.insert(start_point.clone());
+impl CaveSet for u64 {
+    fn empty(_: usize) -> Self {
+        0
     }
 
+    fn contains(&self, cave: usize) -> bool {
+        self & (1 << cave) != 0
+    }
 
-    for cave in connections.get(start_point).unwrap() {
-        if visited.contains(cave) {
-            continue;
-        } else if cave == "end" {
-            n_paths += 1;
-        } else {
-            n_paths += find_paths(&cave, &connections, &mut visited.clone());
-        }
+    fn insert(&mut self, cave: usize) {
+        *self |= 1 << cave;
     }
-    n_paths
 }
 
+impl CaveSet for Vec<u64> {
+    fn empty(n_caves: usize) -> Self {
+        vec![0; n_caves.div_ceil(64)]
+    }
 
-fn find_paths_2(start_point: &String, connections: &Input, visited: &mut HashSet<String>, small_seen_twice: bool) -> usize {
-    let mut n_paths = 0;
-    let mut small_seen_twice = small_seen_twice;
+    fn contains(&self, cave: usize) -> bool {
+        self[cave / 64] & (1 << (cave % 64)) != 0
+    }
 
-    if is_ascii_lowercase(&start_point) {
-        if visited.contains(start_point) {
-            small_seen_twice = true;
-        } else {
-            visited.insert(start_point.clone());
-        }
+    fn insert(&mut self, cave: usize) {
+        self[cave / 64] |= 1 << (cave % 64);
     }
+}
+
+/// A path from start that is about to enter cave
+#[derive(Debug, Clone)]
+struct PartialPath<S> {
+    cave: usize,
+    visited: S,
+    small_seen_twice: bool,
+}
 
+impl<S: CaveSet> PartialPath<S> {
+    // Enter the cave and call f with every path continuing to a neighbor,
+    // returns the number of paths that end right after this cave
+    fn extend(&self, caves: &Caves, mut f: impl FnMut(PartialPath<S>)) -> usize {
+        let mut n_paths = 0;
+        let mut small_seen_twice = self.small_seen_twice;
+        let mut visited = self.visited.clone();
+
+        if caves.small[self.cave] {
+            if visited.contains(self.cave) {
+                small_seen_twice = true;
+            } else {
+                visited.insert(self.cave);
+            }
+        }
 
-    for cave in connections.get(start_point).unwrap() {
-        if (visited.contains(cave) && small_seen_twice) || cave == "start" {
-            continue;
-        } else if cave == "end" {
-            n_paths += 1;
-        } else {
-            n_paths += find_paths_2(&cave, &connections, &mut visited.clone(), small_seen_twice);
+        for &next in &caves.connections[self.cave] {
+            if (visited.contains(next) && small_seen_twice) || next == caves.start {
+                continue;
+            } else if next == caves.end {
+                n_paths += 1;
+            } else {
+                f(PartialPath{cave: next, visited: visited.clone(), small_seen_twice});
+            }
         }
+        n_paths
     }
-    n_paths
 }
 
+fn find_paths<S: CaveSet>(caves: &Caves, path: PartialPath<S>) -> usize {
+    let mut n_continued = 0;
+    let n_finished = path.extend(caves, |next| n_continued += find_paths(caves, next));
+    n_finished + n_continued
+}
+
+/// Same as count_paths, with the visited caves of each path stored in S
+pub fn count_paths_with<S: CaveSet>(caves: &Caves, small_seen_twice: bool, n_threads: usize) -> usize {
+    let mut n_paths = 0;
+    let visited = S::empty(caves.names.len());
+    let mut frontier = vec![PartialPath{cave: caves.start, visited, small_seen_twice}];
+
+    while n_threads > 1 && !frontier.is_empty() && frontier.len() < 8 * n_threads {
+        let mut next_frontier = Vec::new();
+        for path in &frontier {
+            n_paths += path.extend(caves, |next| next_frontier.push(next));
+        }
+        frontier = next_frontier;
+    }
 
+    n_paths + parallel::map(&frontier, n_threads, |path| find_paths(caves, path.clone())).iter().sum::<usize>()
+}
 
-fn part1(connections: &Input) -> usize {
-    find_paths(&String::from("start"), &connections, &mut HashSet::new())
+/// Number of paths from start to end, a single small cave may be visited twice unless small_seen_twice.
+/// The paths are expanded breadth first until there are enough independent subtrees for n_threads threads.
+pub fn count_paths(caves: &Caves, small_seen_twice: bool, n_threads: usize) -> usize {
+    if caves.names.len() <= 64 {
+        count_paths_with::<u64>(caves, small_seen_twice, n_threads)
+    } else {
+        count_paths_with::<Vec<u64>>(caves, small_seen_twice, n_threads)
+    }
 }
 
-fn part2(connections: &Input) -> usize {
-    find_paths_2(&String::from("start"), &connections, &mut HashSet::new(), false)
+/// Number of paths from start to end visiting small caves at most once
+pub fn part1(caves: &Caves) -> usize {
+    count_paths(caves, true, parallel::n_threads())
 }
 
-pub fn day12() {
+/// Number of paths from start to end visiting a single small cave twice at most
+pub fn part2(caves: &Caves) -> usize {
+    count_paths(caves, false, parallel::n_threads())
+}
+
+pub fn day12() -> error::Result<()> {
     let input = input::get_input(12);
-    let connections = parse_input(&input);
+    let caves = memory::record("parse", || parse(&input))?;
 
     let t0 = Instant::now();
-    println!("Part1: {}", part1(&connections));
-    println!("Part2: {}", part2(&connections));
+    println!("Part1: {}", memory::record("part1", || part1(&caves)));
+    println!("Part2: {}", memory::record("part2", || part2(&caves)));
     println!("Time: {} us", t0.elapsed().as_micros());
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::generate::generate;
+
+    #[test]
+    fn many_caves() {
+        // a chain through 70 small caves, each with a big cave to step aside
+        let mut input = String::from("start-c0\nc69-end\n");
+        for i in 0..70 {
+            if i > 0 {
+                input += &format!("c{}-c{}\n", i - 1, i);
+            }
+            input += &format!("c{}-B{}\n", i, i);
+        }
+        let caves = super::parse(&input).unwrap();
+        assert_eq!(caves.names.len(), 142);
+        assert_eq!(super::part1(&caves), 1);
+        // step aside and return to one of the small caves
+        assert_eq!(super::part2(&caves), 1 + 70);
+
+        let input = generate(12, Some(500), 1).unwrap();
+        let caves = super::parse(&input).unwrap();
+        assert!(caves.names.len() > 64);
+        assert!(super::part2(&caves) > super::part1(&caves));
+    }
+
+    #[test]
+    fn wide_sets() {
+        for seed in 1..=3 {
+            let input = generate(12, Some(12), seed).unwrap();
+            let caves = super::parse(&input).unwrap();
+            for small_seen_twice in [true, false] {
+                assert_eq!(
+                    super::count_paths_with::<u64>(&caves, small_seen_twice, 1),
+                    super::count_paths_with::<Vec<u64>>(&caves, small_seen_twice, 2)
+                );
+            }
+        }
+    }
 }
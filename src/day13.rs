@@ -1,48 +1,61 @@
+//! Day 13: Transparent Origami
+use crate::animate::Frame;
+use crate::error::{self, OrInvalid};
+use crate::fxhash::{FxBuildHasher, FxHashSet};
 use crate::input;
+use crate::memory;
+use crate::log::{debug, trace};
 use std::time::Instant;
 use std::collections::HashSet;
+use std::hash::BuildHasher;
 use itertools::Itertools;
 
-enum Axis {
+#[derive(Debug)]
+pub enum Axis {
     X,
     Y
 }
 
 
-struct Input {
-    dots: Vec<(u32, u32)>,
-    folds: Vec<(Axis, u32)>,
+/// The dots on the transparent paper and the fold instructions
+pub struct Input {
+    pub dots: Vec<(u32, u32)>,
+    pub folds: Vec<(Axis, u32)>,
 }
 
-fn parse_input(input: &String) -> Input {
-    let mut iter = input.split("\n\n");
-    let dots = iter
-        .next().unwrap()
-        .lines()
-        .map(|l| l.split(",").map(str::parse).map(Result::unwrap).collect_tuple().unwrap())
-        .collect();
+fn parse_dot(line: &str) -> error::Result<(u32, u32)> {
+    let (x, y) = line.split_once(',').or_invalid("expected a dot x,y")?;
+    Ok((x.parse().or_invalid("invalid x")?, y.parse().or_invalid("invalid y")?))
+}
+
+fn parse_fold(line: &str) -> error::Result<(Axis, u32)> {
+    let (axis, coord) = line
+        .strip_prefix("fold along ")
+        .and_then(|fold| fold.split_once('='))
+        .or_invalid("expected a fold along x=... or y=...")?;
+
+    let axis = match axis {
+        "x" => Axis::X,
+        "y" => Axis::Y,
+        _ => return Err(error::Error::Parse(format!("unknown axis {:?}", axis))),
+    };
+    Ok((axis, coord.parse().or_invalid("invalid fold position")?))
+}
 
-    let folds = iter
-        .next().unwrap()
-        .lines()
-        .map(|l| {
-            let mut iter = l.split("=");
-            let axis = match iter.next().unwrap().chars().last() {
-                Some('x') => Axis::X,
-                Some('y') => Axis::Y,
-                _ => panic!("Could not parse axis"),
-            };
-            let coord = iter.next().unwrap().parse().unwrap();
-            (axis, coord)
-        })
-        .collect();
+/// Parse the dots and the fold instructions, separated by an empty line
+pub fn parse(input: &str) -> error::Result<Input> {
+    let (dots, folds) = input.split_once("\n\n").or_invalid("expected dots and folds separated by an empty line")?;
 
-    return Input{dots, folds};
+    Ok(Input{
+        dots: input::parse_lines(dots, parse_dot)?,
+        folds: input::parse_lines(folds, parse_fold)?,
+    })
 }
 
 
-fn apply_fold(grid: &HashSet<(u32, u32)>, axis: &Axis, position: u32) -> HashSet<(u32, u32)>{
-    let mut new_grid = HashSet::new();
+/// Fold the paper along the given line, dots on top of each other are merged
+pub fn apply_fold<S: BuildHasher + Default>(grid: &HashSet<(u32, u32), S>, axis: &Axis, position: u32) -> HashSet<(u32, u32), S>{
+    let mut new_grid = HashSet::default();
 
     for (x, y) in grid.iter().copied() {
         match axis {
@@ -51,7 +64,6 @@ fn apply_fold(grid: &HashSet<(u32, u32)>, axis: &Axis, position: u32) -> HashSet
                     new_grid.insert((x, y));
                 } else {
                     let new_x = position - (x - position);
-                    // println!("Folding {} {} -> {} {}", x, y, new_x, y);
                     new_grid.insert((new_x, y));
                 }
             },
@@ -60,7 +72,6 @@ fn apply_fold(grid: &HashSet<(u32, u32)>, axis: &Axis, position: u32) -> HashSet
                     new_grid.insert((x, y));
                 } else {
                     let new_y = position - (y - position);
-                    // println!("Folding {} {} -> {} {}", x, y, x, new_y);
                     new_grid.insert((x, new_y));
                 }
             },
@@ -70,40 +81,84 @@ fn apply_fold(grid: &HashSet<(u32, u32)>, axis: &Axis, position: u32) -> HashSet
     new_grid
 }
 
-fn part1(input: &Input) -> usize {
-    let mut grid: HashSet<(u32, u32)> = input.dots.iter().copied().collect();
+/// Number of dots visible after the first fold
+pub fn part1(input: &Input) -> usize {
+    let grid: FxHashSet<(u32, u32)> = input.dots.iter().copied().collect();
     let (axis, position) = input.folds.first().unwrap();
-    grid = apply_fold(&mut grid, axis, *position);
-    grid.len()
+    apply_fold(&grid, axis, *position).len()
 }
 
-fn part2(input: &Input) {
-    let mut grid: HashSet<(u32, u32)> = input.dots.iter().copied().collect();
-    for (axis, position) in &input.folds {
-        grid = apply_fold(&mut grid, axis, *position);
-    }
+fn grid_width<S>(grid: &HashSet<(u32, u32), S>) -> u32 {
+    grid.iter().map(|(x, _)| *x).max().unwrap_or(0) + 1
+}
 
-    let n_cols = grid.iter().map(|(x, _)| *x).max().unwrap() + 1;
-    let n_rows = grid.iter().map(|(_, y)| *y).max().unwrap() + 1;
+fn grid_height<S>(grid: &HashSet<(u32, u32), S>) -> u32 {
+    grid.iter().map(|(_, y)| *y).max().unwrap_or(0) + 1
+}
+
+/// Draw the dots, one line per row
+pub fn render_grid<S>(grid: &HashSet<(u32, u32), S>) -> String {
+    let n_cols = grid_width(grid);
+    let n_rows = grid_height(grid);
     let mut display = vec![vec![' '; n_cols as usize]; n_rows as usize];
 
-    for (x, y) in grid {
+    for (x, y) in grid.iter().copied() {
         display[y as usize][x as usize] = '▇';
     }
 
-    for row in display {
-        println!("{}", row.iter().copied().join(""));
+    display.iter().map(|row| row.iter().collect::<String>() + "\n").join("")
+}
+
+/// The dots before the first fold and after each fold
+pub struct Snapshot {
+    pub n_folds: usize,
+    pub grid: FxHashSet<(u32, u32)>,
+}
+
+impl Frame for Snapshot {
+    fn render(&self) -> String {
+        format!("{} folds, {} dots\n\n{}", self.n_folds, self.grid.len(), render_grid(&self.grid))
+    }
+}
+
+pub fn snapshots(input: &Input) -> impl Iterator<Item = Snapshot> + '_ {
+    let grid: FxHashSet<(u32, u32)> = input.dots.iter().copied().collect();
+    let initial = Snapshot{n_folds: 0, grid: grid.clone()};
+
+    let folded = input.folds.iter().enumerate().scan(grid, |grid, (i, (axis, position))| {
+        *grid = apply_fold(grid, axis, *position);
+        Some(Snapshot{n_folds: i + 1, grid: grid.clone()})
+    });
+
+    std::iter::once(initial).chain(folded)
+}
+
+/// The code drawn by the dots after all folds
+pub fn part2(input: &Input) -> String {
+    render_grid(&fold_all::<FxBuildHasher>(input))
+}
+
+/// The dots after all folds
+pub fn fold_all<S: BuildHasher + Default>(input: &Input) -> HashSet<(u32, u32), S> {
+    let mut grid: HashSet<(u32, u32), S> = input.dots.iter().copied().collect();
+    debug!("{} dots, {} folds", grid.len(), input.folds.len());
+
+    for (axis, position) in &input.folds {
+        grid = apply_fold(&grid, axis, *position);
+        let axis_name = match axis { Axis::X => 'x', Axis::Y => 'y' };
+        trace!("{} dots in a {}x{} grid after folding along {}={}", grid.len(), grid_width(&grid), grid_height(&grid), axis_name, position);
     }
+    grid
 }
 
-pub fn day13() {
+pub fn day13() -> error::Result<()> {
     let input = input::get_input(13);
-    let manual = parse_input(&input);
-    println!("{}", manual.dots.len());
+    let manual = memory::record("parse", || parse(&input))?;
 
     let t0 = Instant::now();
-    println!("Part1: {}", part1(&manual));
+    println!("Part1: {}", memory::record("part1", || part1(&manual)));
     println!("Part2:");
-    part2(&manual);
+    print!("{}", memory::record("part2", || part2(&manual)));
     println!("Time: {} us", t0.elapsed().as_micros());
+    Ok(())
 }
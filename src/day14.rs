@@ -1,88 +1,124 @@
+//! Day 14: Extended Polymerization
+use crate::error::{self, answer, ensure, OrInvalid, OrOverflow};
+use crate::fxhash::{FxBuildHasher, FxHashMap, FxHashSet};
 use crate::input;
+use crate::memory;
 use std::time::Instant;
 use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
 use itertools::{Itertools,MinMaxResult};
 
-type Pair = (char, char);
+/// Two adjacent elements, elements are single ascii letters
+pub type Pair = (u8, u8);
 
-struct Input {
-    polymer_template: Vec<char>,
-    insertion_rules: HashMap<Pair, (Pair, Pair)>,
+/// The polymer template and the two pairs each pair turns into by an insertion
+pub struct Input<'a> {
+    pub polymer_template: &'a [u8],
+    pub insertion_rules: FxHashMap<Pair, (Pair, Pair)>,
 }
 
-fn parse_input(input: &String) -> Input {
-    let mut iter = input.split("\n\n");
-    let polymer_template = iter.next().unwrap().trim().chars().collect();
+fn parse_rule(line: &str) -> error::Result<(Pair, (Pair, Pair))> {
+    let (pair, replacement) = line.split_once(" -> ").or_invalid("expected a rule AB -> C")?;
+    let pair: Pair = pair.bytes().collect_tuple().or_invalid("rules must start with two elements")?;
+    let replacement = match replacement.as_bytes() {
+        [element] => *element,
+        _ => return Err(error::Error::Parse("rules must insert a single element".to_string())),
+    };
+    Ok((pair, ((pair.0, replacement), (replacement, pair.1))))
+}
 
-    let insertion_rules = iter
-        .next()
-        .unwrap()
-        .lines()
-        .map(|l| {
-            let mut iter = l.split(" -> ");
-            let pair: Pair = iter.next().unwrap().chars().collect_tuple().unwrap();
-            let replacement = iter.next().unwrap().chars().next().unwrap();
-            (pair, ((pair.0, replacement), (replacement, pair.1)))
-        })
-        .collect();
+/// Parse the polymer template and the insertion rules, separated by an empty line
+pub fn parse(input: &str) -> error::Result<Input<'_>> {
+    let (template, rules) = input.split_once("\n\n").or_invalid("expected template and rules separated by an empty line")?;
+    let polymer_template = template.trim().as_bytes();
+    ensure(polymer_template.len() >= 2, "the template needs at least two elements")?;
+
+    let insertion_rules: FxHashMap<Pair, (Pair, Pair)> = input::parse_lines(rules, parse_rule)?.into_iter().collect();
+
+    // every pair that can ever appear in the polymer needs a rule
+    let mut to_check: Vec<Pair> = polymer_template.iter().copied().tuple_windows().collect();
+    let mut seen: FxHashSet<Pair> = FxHashSet::default();
+    while let Some(pair) = to_check.pop() {
+        if seen.insert(pair) {
+            let (left, right) = insertion_rules.get(&pair).or_invalid("missing insertion rule")?;
+            to_check.push(*left);
+            to_check.push(*right);
+        }
+    }
 
-    Input{polymer_template, insertion_rules}
+    Ok(Input{polymer_template, insertion_rules})
+}
 
+fn add_count<K: Hash + Eq, S: BuildHasher>(counter: &mut HashMap<K, u128, S>, key: K, count: u128) -> error::Result<()> {
+    let entry = counter.entry(key).or_insert(0);
+    *entry = entry.checked_add(count).or_overflow()?;
+    Ok(())
 }
 
-fn pair_insertion(counter: &HashMap<Pair, i64>, rules: &HashMap<Pair, (Pair, Pair)>) -> HashMap<Pair, i64> {
-    let mut new_counter = HashMap::new();
+fn pair_insertion<S: BuildHasher + Default>(
+    counter: &HashMap<Pair, u128, S>,
+    rules: &FxHashMap<Pair, (Pair, Pair)>,
+) -> error::Result<HashMap<Pair, u128, S>> {
+    let mut new_counter = HashMap::default();
 
     for (pair, count) in counter {
         let (left, right) = rules[pair];
-        *new_counter.entry(left).or_insert(0) += count;
-        *new_counter.entry(right).or_insert(0) += count;
+        add_count(&mut new_counter, left, *count)?;
+        add_count(&mut new_counter, right, *count)?;
     }
 
-    new_counter
+    Ok(new_counter)
+}
+
+/// Difference between the most and least common element after n_steps insertions
+pub fn polymer_development(input: &Input, n_steps: u16) -> error::Result<u128> {
+    polymer_development_with::<FxBuildHasher>(input, n_steps)
 }
 
-fn polymer_development(input: &Input, n_steps: u16) -> i64 {
-    let mut counter = HashMap::new();
+/// Same as polymer_development, counting the pairs in hash maps using S
+pub fn polymer_development_with<S: BuildHasher + Default>(input: &Input, n_steps: u16) -> error::Result<u128> {
+    let mut counter: HashMap<Pair, u128, S> = HashMap::default();
     for i in 0..(input.polymer_template.len() - 1) {
         let pair = (input.polymer_template[i], input.polymer_template[i + 1]);
         *counter.entry(pair).or_insert(0) += 1;
     }
 
     for _ in 0..n_steps {
-        counter = pair_insertion(&counter, &input.insertion_rules);
+        counter = pair_insertion(&counter, &input.insertion_rules)?;
     }
 
-    let mut char_counter: HashMap<char, i64> = HashMap::new();
+    let mut char_counter: HashMap<u8, u128, S> = HashMap::default();
     for (pair, count) in &counter {
-        *char_counter.entry(pair.0).or_insert(0) += count;
+        add_count(&mut char_counter, pair.0, *count)?;
     }
-    *char_counter.entry(*input.polymer_template.last().unwrap()).or_insert(0) += 1;
+    add_count(&mut char_counter, *input.polymer_template.last().unwrap(), 1)?;
 
 
     let minmax = char_counter.iter().minmax_by_key(|(_, count)| *count);
 
     match minmax {
-        MinMaxResult::MinMax(min, max) => max.1 - min.1,
+        MinMaxResult::MinMax(min, max) => Ok(max.1 - min.1),
+        MinMaxResult::OneElement(_) => Ok(0),
         _ => panic!("Could not find minmax"),
     }
 }
 
 
-fn part1(input: &Input) -> i64 {
+pub fn part1(input: &Input) -> error::Result<u128> {
     polymer_development(input, 10)
 }
 
-fn part2(input: &Input) -> i64 {
+pub fn part2(input: &Input) -> error::Result<u128> {
     polymer_development(input, 40)
 }
 
-pub fn day14() {
+pub fn day14() -> error::Result<()> {
     let input = input::get_input(14);
-    let polymer_rules = parse_input(&input);
+    let polymer_rules = memory::record("parse", || parse(&input))?;
 
     let t0 = Instant::now();
-    println!("Part1: {}", part1(&polymer_rules));
-    println!("Part2: {}", part2(&polymer_rules));
+    println!("Part1: {}", answer(memory::record("part1", || part1(&polymer_rules))));
+    println!("Part2: {}", answer(memory::record("part2", || part2(&polymer_rules))));
     println!("Time: {} us", t0.elapsed().as_micros());
+    Ok(())
 }
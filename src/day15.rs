@@ -1,19 +1,23 @@
+//! Day 15: Chiton
+use crate::algorithms::{self, Algorithm};
+use crate::error;
 use crate::input;
+use crate::memory;
+use crate::log::debug;
 use std::time::Instant;
 use std::cmp::Ordering;
 use std::collections::BinaryHeap;
 
-type Input = Vec<Vec<u8>>;
+/// Risk level of each position in the cave
+pub type Input = Vec<Vec<u8>>;
 type Node = (usize, usize);
 
 const NEIGHBORS: [(i8, i8); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
 
 
-fn parse_input(input: &String) -> Input {
-    input
-        .lines()
-        .map(|l| l.chars().map(|c| c as u8 - b'0').collect())
-        .collect()
+/// Parse the risk levels, one row of digits per line
+pub fn parse(input: &str) -> error::Result<Input> {
+    input::parse_digit_grid(input)
 }
 
 // Taken and adapted from the rust doc binary_heap example
@@ -60,67 +64,164 @@ fn tiled_risk(row: usize, col: usize, n_tiles: usize, risk: &Input) -> u64 {
 }
 
 
-fn dijkstra(risk: &Input, n_tiles: usize) -> u64 {
+// The neighbors of a position inside a cave of n_rows x n_cols
+fn neighbors(position: Node, n_rows: usize, n_cols: usize) -> impl Iterator<Item = Node> {
+    let (row, col) = position;
+    NEIGHBORS
+        .iter()
+        .filter(move |(drow, dcol)| {
+            !((*drow == -1 && row == 0) || (*dcol == -1 && col == 0) || (*drow == 1 && row == (n_rows - 1)) || (*dcol == 1 && col == (n_cols - 1)))
+        })
+        .map(move |(drow, dcol)| ((row as i64 + *drow as i64) as usize, (col as i64 + *dcol as i64) as usize))
+}
+
+// Dijkstra from the top left to the bottom right, returns the lowest total risk and
+// the predecessor of every position settled on the way. Positions are visited in the order
+// of their risk plus the heuristic, which must not overestimate the risk left to the target
+fn search(risk: &Input, n_tiles: usize, heuristic: impl Fn(Node) -> u64) -> (u64, Vec<Vec<Option<Node>>>) {
     let n_rows = risk.len() * n_tiles;
     let n_cols = risk[0].len() * n_tiles;
 
     let mut dist = vec![vec![u64::MAX; n_cols]; n_rows];
+    let mut previous = vec![vec![None; n_cols]; n_rows];
 
     let target = (n_rows - 1, n_cols - 1);
 
     let mut heap = BinaryHeap::new();
+    let mut max_queue_size = 0;
+    let mut n_visited = 0;
 
     // setup start point
-    heap.push(State{cost: 0, position: (0, 0)});
+    heap.push(State{cost: heuristic((0, 0)), position: (0, 0)});
     dist[0][0] = 0;
 
-    while let Some(State{cost, position}) = heap.pop() {
+    while let Some(State{cost: priority, position}) = heap.pop() {
         let (row, col) = position;
+        let cost = dist[row][col];
+
+        if position == target {
+            debug!("{}x{} cave, visited {} positions, max queue size {}", n_rows, n_cols, n_visited, max_queue_size);
+            return (cost, previous);
+        }
 
-        if position == target {return cost; }
 
+        if priority > cost + heuristic(position) { continue; }
+        n_visited += 1;
 
-        if cost > dist[row][col] { continue; }
+        for (next_row, next_col) in neighbors(position, n_rows, n_cols) {
+            let next_cost = cost + tiled_risk(next_row, next_col, n_tiles, risk);
 
-        for (drow, dcol) in NEIGHBORS {
-            if (drow == -1 && row == 0) || (dcol == -1 && col == 0) || (drow == 1 && row == (n_rows - 1)) || (dcol == 1 && col == (n_cols - 1)) {
-                continue;
+            if next_cost < dist[next_row][next_col] {
+                heap.push(State{cost: next_cost + heuristic((next_row, next_col)), position: (next_row, next_col)});
+                dist[next_row][next_col] = next_cost;
+                previous[next_row][next_col] = Some(position);
+                max_queue_size = max_queue_size.max(heap.len());
             }
+        }
+    }
+
+    (0, previous)
+}
+
+/// Lowest total risk from the top left to the bottom right of the cave,
+/// which is made up of n_tiles x n_tiles copies of the risk map
+pub fn dijkstra(risk: &Input, n_tiles: usize) -> u64 {
+    search(risk, n_tiles, |_| 0).0
+}
+
+/// Same as dijkstra, but preferring positions closer to the target
+pub fn astar(risk: &Input, n_tiles: usize) -> u64 {
+    let n_rows = risk.len() * n_tiles;
+    let n_cols = risk[0].len() * n_tiles;
+
+    // every step costs at least the lowest risk, tiles past the first can wrap around to 1
+    let mut min_risk = risk.iter().flatten().copied().min().unwrap() as u64;
+    if n_tiles > 1 {
+        min_risk = min_risk.min(1);
+    }
+
+    search(risk, n_tiles, |(row, col)| ((n_rows - 1 - row) + (n_cols - 1 - col)) as u64 * min_risk).0
+}
+
+/// Same as dijkstra, but with a ring of buckets as queue, which works as the risk of a step is at most 9
+pub fn bucket_queue(risk: &Input, n_tiles: usize) -> u64 {
+    let n_rows = risk.len() * n_tiles;
+    let n_cols = risk[0].len() * n_tiles;
+    let target = (n_rows - 1, n_cols - 1);
+
+    let mut dist = vec![vec![u64::MAX; n_cols]; n_rows];
+    // all queued positions are at most 9 more than the current risk, so 10 buckets never collide
+    let mut buckets: Vec<Vec<Node>> = vec![Vec::new(); 10];
+    let mut n_queued = 1;
 
-            let next_row = (row as i64 + drow as i64) as usize;
-            let next_col = (col as i64 + dcol as i64) as usize;
-            let next_cost = tiled_risk(next_row, next_col, n_tiles, risk);
+    buckets[0].push((0, 0));
+    dist[0][0] = 0;
 
-            let next = State{
-                cost: cost + next_cost,
-                position: (next_row, next_col),
-            };
+    let mut cost = 0;
+    while n_queued > 0 {
+        while let Some(position) = buckets[(cost % 10) as usize].pop() {
+            n_queued -= 1;
+            let (row, col) = position;
+            if dist[row][col] != cost { continue; }
+            if position == target {
+                return cost;
+            }
 
-            if next.cost < dist[next_row][next_col] {
-                heap.push(next);
-                dist[next_row][next_col] = next.cost;
+            for (next_row, next_col) in neighbors(position, n_rows, n_cols) {
+                let next_cost = cost + tiled_risk(next_row, next_col, n_tiles, risk);
+                if next_cost < dist[next_row][next_col] {
+                    dist[next_row][next_col] = next_cost;
+                    buckets[(next_cost % 10) as usize].push((next_row, next_col));
+                    n_queued += 1;
+                }
             }
         }
+        cost += 1;
     }
 
     0
 }
 
+/// The positions of a path with the lowest total risk, from the top left to the bottom right
+pub fn lowest_risk_path(risk: &Input, n_tiles: usize) -> Vec<(usize, usize)> {
+    let (_, previous) = search(risk, n_tiles, |_| 0);
+
+    let mut position = (risk.len() * n_tiles - 1, risk[0].len() * n_tiles - 1);
+    let mut path = vec![position];
+    while let Some(next) = previous[position.0][position.1] {
+        position = next;
+        path.push(position);
+    }
+
+    path.reverse();
+    path
+}
+
 
-fn part1(input: &Input) -> u64 {
+pub fn part1(input: &Input) -> u64 {
     dijkstra(input, 1)
 }
 
-fn part2(input: &Input) -> u64 {
+pub fn part2(input: &Input) -> u64 {
     dijkstra(input, 5)
 }
 
-pub fn day15() {
+/// Dijkstra with a binary heap, A* and Dijkstra with a bucket queue
+pub const ALGORITHMS: &[Algorithm<Input, u64>] = &[
+    Algorithm { name: "dijkstra", part1, part2 },
+    Algorithm { name: "astar", part1: |risk| astar(risk, 1), part2: |risk| astar(risk, 5) },
+    Algorithm { name: "bucket-queue", part1: |risk| bucket_queue(risk, 1), part2: |risk| bucket_queue(risk, 5) },
+];
+
+pub fn day15() -> error::Result<()> {
     let input = input::get_input(15);
-    let risk = parse_input(&input);
+    let risk = memory::record("parse", || parse(&input))?;
+
+    let algorithm = algorithms::select(ALGORITHMS)?;
 
     let t0 = Instant::now();
-    println!("Part1: {}", part1(&risk));
-    println!("Part2: {}", part2(&risk));
+    println!("Part1: {}", memory::record("part1", || (algorithm.part1)(&risk)));
+    println!("Part2: {}", memory::record("part2", || (algorithm.part2)(&risk)));
     println!("Time: {} us", t0.elapsed().as_micros());
+    Ok(())
 }
@@ -1,4 +1,7 @@
+//! Day 16: Packet Decoder
+use crate::error::{self, answer, ensure, OrInvalid, OrOverflow};
 use crate::input;
+use crate::memory;
 use std::time::Instant;
 use phf::{Map, phf_map};
 
@@ -10,47 +13,51 @@ const HEX2BITS: Map<char, [u8; 4]> = phf_map! {
     'C' => [1, 1, 0, 0], 'D' => [1, 1, 0, 1], 'E' => [1, 1, 1, 0], 'F' => [1, 1, 1, 1],
 };
 
-struct Header {
-    version: u8,
-    type_id: u8,
+pub struct Header {
+    pub version: u8,
+    pub type_id: u8,
 }
 
-struct Literal {
-    header: Header,
-    value: u64,
+pub struct Literal {
+    pub header: Header,
+    pub value: u128,
 }
 
 impl Literal {
-    fn eval(&self) -> u64 {self.value}
+    pub fn eval(&self) -> error::Result<u128> {Ok(self.value)}
 }
 
-struct Operator {
-    header: Header,
-    packets: Vec<Packet>
+/// An operator packet, the type id determines the operation applied to the sub-packets
+pub struct Operator {
+    pub header: Header,
+    pub packets: Vec<Packet>
 }
 
 impl Operator {
-    fn eval(&self) -> u64 {
+    pub fn eval(&self) -> error::Result<u128> {
+        let values = self.packets.iter().map(|p| p.eval()).collect::<error::Result<Vec<_>>>()?;
+
         match self.header.type_id {
-            0 => self.packets.iter().map(|p| p.eval()).sum(),
-            1 => self.packets.iter().map(|p| p.eval()).product(),
-            2 => self.packets.iter().map(|p| p.eval()).min().unwrap(),
-            3 => self.packets.iter().map(|p| p.eval()).max().unwrap(),
-            5 => (self.packets[0].eval() > self.packets[1].eval()) as u64,
-            6 => (self.packets[0].eval() < self.packets[1].eval()) as u64,
-            7 => (self.packets[0].eval() == self.packets[1].eval()) as u64,
+            0 => values.iter().try_fold(0u128, |sum, v| sum.checked_add(*v)).or_overflow(),
+            1 => values.iter().try_fold(1u128, |product, v| product.checked_mul(*v)).or_overflow(),
+            2 => Ok(*values.iter().min().unwrap()),
+            3 => Ok(*values.iter().max().unwrap()),
+            5 => Ok((values[0] > values[1]) as u128),
+            6 => Ok((values[0] < values[1]) as u128),
+            7 => Ok((values[0] == values[1]) as u128),
             _ => panic!("Unexpected type {}", self.header.type_id)
         }
     }
 }
 
-enum Packet {
+pub enum Packet {
     Literal(Literal),
     Operator(Operator),
 }
 
 impl Packet {
-    fn eval(&self) -> u64 {
+    /// The value of the expression represented by this packet
+    pub fn eval(&self) -> error::Result<u128> {
         match self {
             Packet::Literal(lit) => lit.eval(),
             Packet::Operator(op) => op.eval(),
@@ -58,99 +65,143 @@ impl Packet {
     }
 }
 
-type BitStream = Vec<u8>;
+/// The transmission as a sequence of single bits
+pub type BitStream = Vec<u8>;
 
-fn parse_int(input: &BitStream, pos: &mut usize, bits: u8) -> u16 {
+fn parse_int(input: &BitStream, pos: &mut usize, bits: u8) -> error::Result<u16> {
     let mut val: u16 = 0;
     for bit in (0..bits).rev() {
-        val |= (input[*pos] as u16) << bit;
+        val |= (*input.get(*pos).or_invalid("transmission ended inside a packet")? as u16) << bit;
         *pos += 1;
     }
-    val
+    Ok(val)
 }
 
-fn parse_header(input: &BitStream, pos: &mut usize) -> Header {
-    let version = parse_int(input, pos, 3) as u8;
-    let type_id = parse_int(input, pos, 3) as u8;
-    Header{version, type_id}
+fn parse_header(input: &BitStream, pos: &mut usize) -> error::Result<Header> {
+    let version = parse_int(input, pos, 3)? as u8;
+    let type_id = parse_int(input, pos, 3)? as u8;
+    Ok(Header{version, type_id})
 }
 
-fn parse_input(input: &String) -> BitStream {
-    input.trim().chars().map(|c| HEX2BITS[&c]).flatten().collect()
+/// Convert the hexadecimal transmission into bits
+pub fn hex_to_bits(input: &str) -> error::Result<BitStream> {
+    let mut bits = Vec::new();
+    for c in input.trim().chars() {
+        bits.extend(HEX2BITS.get(&c).or_invalid("expected hexadecimal digits")?);
+    }
+    Ok(bits)
 }
 
-fn parse_literal(input: &BitStream, pos: &mut usize) -> u64 {
+fn parse_literal(input: &BitStream, pos: &mut usize) -> error::Result<u128> {
     let mut last = false;
 
-    let mut parts = Vec::new();
+    let mut value: u128 = 0;
     while !last {
-        last = input[*pos] == 0;
-        *pos += 1;
-        parts.push(parse_int(input, pos, 4));
+        last = parse_int(input, pos, 1)? == 0;
+        let part = parse_int(input, pos, 4)? as u128;
+        value = value.checked_mul(16).or_invalid("literal does not fit into an u128")? | part;
     }
 
-    let val = parts.iter().rev().enumerate().map(|(n, v)| (*v as u64) << (4 * n)).sum();
+    Ok(value)
+}
+
+// Real transmissions are nested a few levels deep, this keeps the recursion of
+// parsing, evaluating and dropping packets far from the end of the stack
+pub(crate) const MAX_DEPTH: usize = 256;
 
-    val
+/// Decode the packet starting at pos, pos is moved past its end
+pub fn parse_packet(input: &BitStream, pos: &mut usize) -> error::Result<Packet> {
+    parse_nested(input, pos, 0)
 }
 
-fn parse_packet(input: &BitStream, pos: &mut usize) -> Packet {
-    let header = parse_header(input, pos);
+fn parse_nested(input: &BitStream, pos: &mut usize, depth: usize) -> error::Result<Packet> {
+    ensure(depth < MAX_DEPTH, "packets are nested too deep")?;
+    let header = parse_header(input, pos)?;
 
-    let packet = if header.type_id == 4 {
-        let lit = Literal{header, value: parse_literal(input, pos)};
-        Packet::Literal(lit)
+    if header.type_id == 4 {
+        let lit = Literal{header, value: parse_literal(input, pos)?};
+        Ok(Packet::Literal(lit))
     } else {
 
-        let size_in_bits = input[*pos] == 0;
-        *pos += 1;
+        let size_in_bits = parse_int(input, pos, 1)? == 0;
 
         let mut packets = Vec::new();
 
         if size_in_bits {
-            let size = parse_int(input, pos, 15) as usize;
+            let size = parse_int(input, pos, 15)? as usize;
             let end = *pos + size;
 
             while *pos < end {
-                packets.push(parse_packet(input, pos));
+                packets.push(parse_nested(input, pos, depth + 1)?);
             }
+            ensure(*pos == end, "sub-packets do not match the given length")?;
         } else {
-            let size = parse_int(input, pos, 11) as usize;
+            let size = parse_int(input, pos, 11)? as usize;
 
             while packets.len() < size {
-                packets.push(parse_packet(input, pos));
+                packets.push(parse_nested(input, pos, depth + 1)?);
             }
 
         }
 
-        Packet::Operator(Operator{header, packets})
-    };
+        match header.type_id {
+            0..=3 => ensure(!packets.is_empty(), "operators need at least one sub-packet")?,
+            _ => ensure(packets.len() == 2, "comparisons need exactly two sub-packets")?,
+        }
+        Ok(Packet::Operator(Operator{header, packets}))
+    }
+}
 
-    packet
+/// Decode the outermost packet of the hexadecimal transmission
+pub fn parse(input: &str) -> error::Result<Packet> {
+    parse_packet(&hex_to_bits(input)?, &mut 0)
 }
 
-fn version_sum(packet: &Packet) -> u64 {
+/// Sum of the versions of the packet and all its sub-packets
+pub fn version_sum(packet: &Packet) -> u64 {
     match packet {
         Packet::Literal(lit) => lit.header.version as u64,
         Packet::Operator(op) => op.header.version as u64 + op.packets.iter().map(version_sum).sum::<u64>()
     }
 }
 
-fn part1(packet: &Packet) -> u64 {
+pub fn part1(packet: &Packet) -> u64 {
     version_sum(packet)
 }
 
-fn part2(packet: &Packet) -> u64 {
+pub fn part2(packet: &Packet) -> error::Result<u128> {
     packet.eval()
 }
 
-pub fn day16() {
+pub fn day16() -> error::Result<()> {
     let input = input::get_input(16);
-    let bitstream = parse_input(&input);
-    let packet = parse_packet(&bitstream, &mut 0);
+    let packet = memory::record("parse", || parse(&input))?;
 
     let t0 = Instant::now();
-    println!("Part1: {}", part1(&packet));
-    println!("Part2: {}", part2(&packet));
+    println!("Part1: {}", memory::record("part1", || part1(&packet)));
+    println!("Part2: {}", answer(memory::record("part2", || part2(&packet))));
     println!("Time: {} us", t0.elapsed().as_micros());
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    // Sum operators with a single sub-packet each, nested n deep around the literal 1
+    fn nested(n: usize) -> String {
+        let mut bits = "000000100000000001".repeat(n);
+        bits.push_str("00010000001");
+        while !bits.len().is_multiple_of(4) {
+            bits.push('0');
+        }
+        bits.as_bytes().chunks(4).map(|c| format!("{:X}", u8::from_str_radix(std::str::from_utf8(c).unwrap(), 2).unwrap())).collect()
+    }
+
+    #[test]
+    fn nesting() {
+        let packet = super::parse(&nested(super::MAX_DEPTH - 1)).unwrap();
+        assert_eq!(super::part2(&packet).unwrap(), 1);
+
+        let error = super::parse(&nested(200_000)).err().unwrap();
+        assert_eq!(error.to_string(), "Invalid input, packets are nested too deep");
+    }
 }
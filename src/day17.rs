@@ -1,34 +1,39 @@
+//! Day 17: Trick Shot
+use crate::error::{self, ensure, OrInvalid};
 use crate::input;
+use crate::memory;
+use crate::parallel;
 use std::time::Instant;
 use lazy_static::lazy_static;
 use regex::Regex;
-use itertools::Itertools;
 use std::cmp::Ordering;
 
 #[derive(Debug)]
-struct TargetArea {
-    min_x: i32,
-    max_x: i32,
-    min_y: i32,
-    max_y: i32
+pub struct TargetArea {
+    pub min_x: i32,
+    pub max_x: i32,
+    pub min_y: i32,
+    pub max_y: i32
 }
 
 impl TargetArea {
-    fn is_inside(&self, x: i32, y: i32) -> bool {
-        (x >= self.min_x)
-        && (x <= self.max_x)
-        && (y >= self.min_y)
-        && (y <= self.max_y)
+    pub fn is_inside(&self, x: i64, y: i64) -> bool {
+        (x >= self.min_x as i64)
+        && (x <= self.max_x as i64)
+        && (y >= self.min_y as i64)
+        && (y <= self.max_y as i64)
     }
 
 
-    fn hits(&self, vx: i32, vy: i32) -> bool {
+    /// Whether the probe launched with this initial velocity is inside the area after any step,
+    /// positions are tracked in i64 so that they cannot overflow for any i32 target area
+    pub fn hits(&self, vx: i64, vy: i64) -> bool {
         let mut x = 0;
         let mut y = 0;
         let mut vx = vx;
         let mut vy = vy;
 
-        while x <= self.max_x && y >= self.min_y {
+        while x <= self.max_x as i64 && y >= self.min_y as i64 {
             if self.is_inside(x, y) {
                 return true;
             } 
@@ -46,51 +51,61 @@ impl TargetArea {
         false
     }
 
-    fn possible_shots(&self) -> (i32, usize) {
-        let mut best_height = 0;
-        
-        let mut n_shots = 0;
+    /// Highest possible y position of a hit and the number of velocities that hit
+    pub fn possible_shots(&self) -> (i64, usize) {
+        self.possible_shots_with(parallel::n_threads())
+    }
 
-        for vx in 1..=self.max_x {
-            for vy in self.min_y..self.min_y.abs() {
-                let height = max_height(vy);
-                if self.hits(vx, vy) {
-                    n_shots += 1;
+    /// Same as possible_shots, the initial x velocities are split over n_threads threads
+    pub fn possible_shots_with(&self, n_threads: usize) -> (i64, usize) {
+        let min_y = self.min_y as i64;
+        let velocities: Vec<i64> = (1..=self.max_x as i64).collect();
 
-                    if height > best_height {best_height = height}
+        let shots = parallel::map(&velocities, n_threads, |vx| {
+            let mut best_height = 0;
+            let mut n_shots = 0;
+
+            for vy in min_y..-min_y {
+                if self.hits(*vx, vy) {
+                    n_shots += 1;
+                    best_height = best_height.max(max_height(vy));
                 }
-                
             }
-        }
+            (best_height, n_shots)
+        });
 
-        (best_height, n_shots)
+        shots.into_iter().fold((0, 0), |(best, n), (height, count)| (best.max(height), n + count))
     }
 
 }
 
-fn parse_input(input: &String) -> TargetArea {
+/// Parse the target area, which must be at positive x and negative y
+pub fn parse(input: &str) -> error::Result<TargetArea> {
     lazy_static! {
         static ref RE: Regex = Regex::new(r"target area: x=(-?\d+)..(-?\d+), y=(-?\d+)..(-?\d+)").unwrap();
     }
 
     let groups = RE.captures(input)
-        .unwrap()
+        .or_invalid("expected target area: x=..., y=...")?
         .iter()
         .skip(1)
-        .map(|m| m.unwrap().as_str())
-        .map(str::parse::<i32>)
-        .map(Result::unwrap)
-        .collect_vec();
+        .map(|m| m.unwrap().as_str().parse::<i32>().or_invalid("invalid coordinate"))
+        .collect::<error::Result<Vec<_>>>()?;
 
-    TargetArea{
+    let target_area = TargetArea{
         min_x: groups[0],
         max_x: groups[1],
         min_y: groups[2],
         max_y: groups[3],
-    }
+    };
+    ensure(target_area.min_x <= target_area.max_x && target_area.min_y <= target_area.max_y, "empty target area")?;
+    // the search for velocities only covers shots forward and down, like in all real inputs
+    ensure(target_area.min_x > 0 && target_area.max_y < 0, "the target area must be ahead of and below the launcher")?;
+    Ok(target_area)
 }
 
-fn max_height(vy: i32) -> i32 {
+/// Highest y position reached with initial vertical velocity vy
+pub fn max_height(vy: i64) -> i64 {
     if vy < 0 {
         0
     } else {
@@ -98,14 +113,40 @@ fn max_height(vy: i32) -> i32 {
     }
 }
 
+pub fn part1(target_area: &TargetArea) -> i64 {
+    target_area.possible_shots().0
+}
 
-pub fn day17() {
+pub fn part2(target_area: &TargetArea) -> usize {
+    target_area.possible_shots().1
+}
+
+
+pub fn day17() -> error::Result<()> {
     let input = input::get_input(17);
-    let target_area = parse_input(&input);
+    let target_area = memory::record("parse", || parse(&input))?;
 
     let t0 = Instant::now();
-    let (part1, part2) = target_area.possible_shots();
+    let (part1, part2) = memory::record("both parts", || target_area.possible_shots());
     println!("Part1: {}", part1);
     println!("Part2: {}", part2);
     println!("Time: {} us", t0.elapsed().as_micros());
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn example() {
+        let target_area = super::parse("target area: x=20..30, y=-10..-5").unwrap();
+        assert_eq!(target_area.possible_shots(), (45, 112));
+    }
+
+    #[test]
+    fn unsupported_areas() {
+        assert!(super::parse("target area: x=-30..-20, y=-10..-5").is_err());
+        assert!(super::parse("target area: x=0..30, y=-10..-5").is_err());
+        assert!(super::parse("target area: x=20..30, y=-10..0").is_err());
+        assert!(super::parse("target area: x=20..30, y=5..10").is_err());
+    }
 }
@@ -1,4 +1,11 @@
+//! Day 18: Snailfish
+use crate::algorithms::{self, Algorithm};
+use crate::error::{self, ensure, OrInvalid};
 use crate::input;
+use crate::memory;
+use crate::parallel;
+use std::fmt;
+use std::ptr;
 use std::time::Instant;
 
 
@@ -72,30 +79,24 @@ impl Node {
         Node::Literal(Literal{value, parent})
     }
 
-    #[allow(dead_code)]
-    fn to_string(&self, arena: &Vec<Node>) -> String {
-        let mut s = String::new();
+    fn fmt_with(&self, arena: &[Node], f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Node::Pair(p) => {
-                let left = &arena[p.left];
-                let right = &arena[p.right];
-                s.push('[');
-                s.push_str(&left.to_string(arena));
-                s.push(',');
-                s.push_str(&right.to_string(arena));
-                s.push(']');
+                write!(f, "[")?;
+                arena[p.left].fmt_with(arena, f)?;
+                write!(f, ",")?;
+                arena[p.right].fmt_with(arena, f)?;
+                write!(f, "]")
             },
-            Node::Literal(l) => s.push_str(&l.value.to_string())
+            Node::Literal(l) => write!(f, "{}", l.value)
         }
-
-        return s;
     }
 
 }
 
 fn shift_adresses(node: &Node, shift: usize) -> Node {
     let s = |p| p + shift;
-    let sp = |p: Option<usize>| p.map_or(None, |v| Some(s(v)));
+    let sp = |p: Option<usize>| p.map(s);
 
     match node {
         Node::Pair(p) => Node::Pair(Pair{left: s(p.left), right: s(p.right), parent: sp(p.parent)}),
@@ -103,28 +104,31 @@ fn shift_adresses(node: &Node, shift: usize) -> Node {
     }
 }
 
+/// A snailfish number, stored as a tree of pairs in an arena with the root at index 0
 #[derive(Debug,Clone)]
-struct Number {
+pub struct Number {
     arena: Vec<Node>
 }
 
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.arena[0].fmt_with(&self.arena, f)
+    }
+}
+
 
 impl Number {
     fn new() -> Number {
         Number{arena: Vec::new()}
     }
 
-    #[allow(dead_code)]
-    fn to_string(&self) -> String {
-        self.arena[0].to_string(&self.arena)
-    }
-
-    fn add(&self, other: &Number) -> Number {
+    /// The reduced sum of both numbers
+    pub fn add(&self, other: &Number) -> Number {
         let mut result = Number::new();
         let len = self.arena.len();
 
         result.arena.push(Node::new_pair(None));
-        let mut pair = result.arena[0].pair_mut().unwrap();
+        let pair = result.arena[0].pair_mut().unwrap();
 
         pair.left = 1;
         pair.right = 1 + len;
@@ -249,8 +253,8 @@ impl Number {
             return [None, None];
         }
 
-        let left = self.find_left_branch(pos).map_or(None, |p| Some(self.descent_right(p)));
-        let right = self.find_right_branch(pos).map_or(None, |p| Some(self.descent_left(p)));
+        let left = self.find_left_branch(pos).map(|p| self.descent_right(p));
+        let right = self.find_right_branch(pos).map(|p| self.descent_left(p));
 
         [left, right]
     }
@@ -323,8 +327,8 @@ impl Number {
         }
     }
 
-    fn magnitude(&self) -> u64 {
-        return self.magnitude_at_pos(0)
+    pub fn magnitude(&self) -> u64 {
+        self.magnitude_at_pos(0)
     }
 
     fn magnitude_at_pos(&self, pos: usize) -> u64 {
@@ -335,59 +339,172 @@ impl Number {
     }
 }
 
-fn parse_number(line: &str) -> Result<Number, ()> {
+// Pairs of input numbers are nested at most four deep, deeper ones could not be reduced
+const MAX_DEPTH: usize = 4;
+
+fn expect_byte(bytes: &[u8], pos: &mut usize, expected: u8) -> error::Result<()> {
+    ensure(bytes.get(*pos) == Some(&expected), &format!("expected {:?}", expected as char))?;
+    *pos += 1;
+    Ok(())
+}
+
+// Parse the pair or single digit literal at pos and add it to the arena, returns its index
+fn parse_node(number: &mut Number, bytes: &[u8], pos: &mut usize, parent: Option<usize>, depth: usize) -> error::Result<usize> {
+    match *bytes.get(*pos).or_invalid("number ended early")? {
+        b'[' => {
+            ensure(depth < MAX_DEPTH, "pairs are nested too deep")?;
+            *pos += 1;
+            number.arena.push(Node::new_pair(parent));
+            let index = number.arena.len() - 1;
+
+            let left = parse_node(number, bytes, pos, Some(index), depth + 1)?;
+            expect_byte(bytes, pos, b',')?;
+            let right = parse_node(number, bytes, pos, Some(index), depth + 1)?;
+            expect_byte(bytes, pos, b']')?;
+
+            let pair = number.arena[index].pair_mut().unwrap();
+            pair.left = left;
+            pair.right = right;
+            Ok(index)
+        },
+        digit @ b'0'..=b'9' => {
+            ensure(parent.is_some(), "a number must be a pair")?;
+            *pos += 1;
+            number.arena.push(Node::new_literal(digit - b'0', parent));
+            Ok(number.arena.len() - 1)
+        },
+        chr => Err(error::Error::Parse(format!("unexpected character {:?}", chr as char))),
+    }
+}
+
+/// Parse a single snailfish number like `[[1,2],3]`
+pub fn parse_number(line: &str) -> error::Result<Number> {
     let mut number = Number::new();
-    let mut node_index = None;
+    let bytes = line.as_bytes();
+    let mut pos = 0;
 
-    for chr in line.chars() {
-        match chr {
-            '[' => {
-                number.arena.push(Node::new_pair(node_index));
-                node_index = Some(number.arena.len() - 1);
-            },
+    parse_node(&mut number, bytes, &mut pos, None, 0)?;
+    ensure(pos == bytes.len(), "unexpected characters after the number")?;
+    Ok(number)
+}
 
-            '0'..='9' => {
-                number.arena.push(Node::new_literal(chr as u8 - b'0', node_index));
-                let index = number.arena.len() - 1;
+/// Parse the homework, one snailfish number per line
+pub fn parse(input: &str) -> error::Result<Vec<Number>> {
+    let numbers = input::parse_lines(input, parse_number)?;
+    ensure(!numbers.is_empty(), "no numbers")?;
+    Ok(numbers)
+}
 
-                let pair = number.arena[node_index.unwrap()].pair_mut().unwrap();
-                match pair.left {
-                    usize::MAX => pair.left = index,
-                    _ => pair.right = index,
-                };
-            },
-            ']' => {
-                let parent_index = number.arena[node_index.unwrap()].parent();
-                if parent_index.is_none() {
-                    continue;
+
+/// Magnitude of the sum of all numbers
+pub fn part1(numbers: &[Number]) -> u64 {
+    let mut number = numbers[0].clone();
+    for other in numbers[1..].iter() {
+        number = number.add(other);
+    }
+
+    number.magnitude()
+}
+
+
+/// Largest magnitude of the sum of any two different numbers
+pub fn part2(numbers: &[Number]) -> u64 {
+    largest_magnitude(numbers, parallel::n_threads())
+}
+
+/// Same as part2, the first summands are split over n_threads threads.
+/// A number is not added to itself, so they are compared by address.
+pub fn largest_magnitude(numbers: &[Number], n_threads: usize) -> u64 {
+    parallel::map(numbers, n_threads, |n1| {
+        numbers.iter().filter(|n2| !ptr::eq(n1, *n2)).map(|n2| n1.add(n2)).map(|n| n.magnitude()).max().unwrap_or(0)
+    })
+    .into_iter()
+    .max()
+    .unwrap_or(0)
+}
+
+/// A snailfish number as its literals from left to right, each with the number of pairs around it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlatNumber {
+    literals: Vec<(u8, u8)>,
+}
+
+impl FlatNumber {
+    fn add_literals(&mut self, number: &Number, pos: usize, depth: u8) {
+        match number.arena[pos] {
+            Node::Literal(l) => self.literals.push((l.value, depth)),
+            Node::Pair(p) => {
+                self.add_literals(number, p.left, depth + 1);
+                self.add_literals(number, p.right, depth + 1);
+            }
+        }
+    }
+
+    /// The reduced sum of both numbers
+    pub fn add(&self, other: &FlatNumber) -> FlatNumber {
+        let literals = self.literals.iter().chain(&other.literals).map(|(value, depth)| (*value, depth + 1)).collect();
+        let mut result = FlatNumber { literals };
+        result.reduce();
+        result
+    }
+
+    fn reduce(&mut self) {
+        loop {
+            // the left literal of a pair nested in four pairs, its right neighbor is the other one of the pair
+            if let Some(i) = self.literals.iter().position(|(_, depth)| *depth as usize > MAX_DEPTH) {
+                let (left, depth) = self.literals[i];
+                let (right, _) = self.literals[i + 1];
+                if i > 0 {
+                    self.literals[i - 1].0 += left;
+                }
+                if let Some(next) = self.literals.get_mut(i + 2) {
+                    next.0 += right;
                 }
+                self.literals[i] = (0, depth - 1);
+                self.literals.remove(i + 1);
+                continue;
+            }
 
-                let parent = number.arena.get_mut(parent_index.unwrap());
-                match parent {
-                    Some(n) => {
-                        let pair = n.pair_mut().unwrap();
-                        match pair.left {
-                            usize::MAX => pair.left = node_index.unwrap(),
-                            _ => pair.right = node_index.unwrap(),
-                        }
-                    }
-                    None => {},
+            if let Some(i) = self.literals.iter().position(|(value, _)| *value >= 10) {
+                let (value, depth) = self.literals[i];
+                self.literals[i] = (value / 2, depth + 1);
+                self.literals.insert(i + 1, (value - value / 2, depth + 1));
+                continue;
+            }
+
+            break;
+        }
+    }
+
+    pub fn magnitude(&self) -> u64 {
+        // neighboring values at the same depth on top of the stack always form a pair
+        let mut stack: Vec<(u64, u8)> = Vec::new();
+        for (value, depth) in &self.literals {
+            let mut top = (*value as u64, *depth);
+            while let Some((left, depth)) = stack.last().copied() {
+                if depth != top.1 {
+                    break;
                 }
-                node_index = parent_index
-            },
-            ',' => {},
-            _ => return Err(())
+                stack.pop();
+                top = (3 * left + 2 * top.0, depth - 1);
+            }
+            stack.push(top);
         }
+        stack[0].0
     }
-    Ok(number)
 }
 
-fn parse_input(input: &String) -> Vec<Number> {
-    input.trim().lines().map(parse_number).map(Result::unwrap).collect()
+impl From<&Number> for FlatNumber {
+    fn from(number: &Number) -> Self {
+        let mut flat = FlatNumber { literals: Vec::new() };
+        flat.add_literals(number, 0, 0);
+        flat
+    }
 }
 
-
-fn part1(numbers: &Vec<Number>) -> u64 {
+/// Same as part1, on flat numbers
+pub fn part1_flat(numbers: &[Number]) -> u64 {
+    let numbers: Vec<FlatNumber> = numbers.iter().map(FlatNumber::from).collect();
     let mut number = numbers[0].clone();
     for other in numbers[1..].iter() {
         number = number.add(other);
@@ -396,22 +513,49 @@ fn part1(numbers: &Vec<Number>) -> u64 {
     number.magnitude()
 }
 
-
-fn part2(numbers: &Vec<Number>) -> u64 {
-    numbers
-        .iter()
-        .map(|n1| numbers.iter().map(|n2| n1.add(n2)).map(|n| n.magnitude()).max().unwrap_or(0))
-        .max()
-        .unwrap_or(0)
+/// Same as part2, on flat numbers
+pub fn part2_flat(numbers: &[Number]) -> u64 {
+    let numbers: Vec<FlatNumber> = numbers.iter().map(FlatNumber::from).collect();
+    parallel::map(&numbers, parallel::n_threads(), |n1| {
+        numbers.iter().filter(|n2| !ptr::eq(n1, *n2)).map(|n2| n1.add(n2).magnitude()).max().unwrap_or(0)
+    })
+    .into_iter()
+    .max()
+    .unwrap_or(0)
 }
 
+/// Numbers as trees in an arena or as flat lists of literals
+pub const ALGORITHMS: &[Algorithm<[Number], u64>] = &[
+    Algorithm { name: "arena", part1, part2 },
+    Algorithm { name: "flat", part1: part1_flat, part2: part2_flat },
+];
+
 
-pub fn day18() {
+pub fn day18() -> error::Result<()> {
     let input = input::get_input(18);
-    let numbers = parse_input(&input);
+    let numbers = memory::record("parse", || parse(&input))?;
+
+    let algorithm = algorithms::select(ALGORITHMS)?;
 
     let t0 = Instant::now();
-    println!("Part1: {}", part1(&numbers));
-    println!("Part2: {}", part2(&numbers));
+    println!("Part1: {}", memory::record("part1", || (algorithm.part1)(&numbers)));
+    println!("Part2: {}", memory::record("part2", || (algorithm.part2)(&numbers)));
     println!("Time: {} us", t0.elapsed().as_micros());
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn different_numbers() {
+        // [9,9] + [9,9] would have the largest magnitude, 225
+        let numbers = super::parse("[9,9]\n[1,1]").unwrap();
+        assert_eq!(super::largest_magnitude(&numbers, 1), 145);
+        for algorithm in super::ALGORITHMS {
+            assert_eq!((algorithm.part2)(&numbers), 145, "{}", algorithm.name);
+        }
+
+        let numbers = super::parse("[9,9]").unwrap();
+        assert_eq!(super::part2(&numbers), 0);
+    }
 }
@@ -1,94 +1,358 @@
+//! Day 2: Dive!
+use crate::error::{self, answer, ensure, OrInvalid, OrOverflow};
 use crate::input;
+use crate::memory;
+use std::fmt;
+use std::io::BufRead;
 
-enum Direction {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
     Up,
     Down,
     Forward,
+    /// Reverse, the opposite of forward
+    Back,
+    /// Stay in place for one step
+    Hold,
 }
 
-struct Command {
-    direction: Direction,
-    amount: i32,
+/// A single line of the planned course, e.g. `forward 5`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Command {
+    pub direction: Direction,
+    pub amount: i32,
 }
 
-struct Position {
-    depth: i32,
-    distance: i32,
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let direction = match self.direction {
+            Direction::Up => "up",
+            Direction::Down => "down",
+            Direction::Forward => "forward",
+            Direction::Back => "back",
+            Direction::Hold => "hold",
+        };
+        write!(f, "{} {}", direction, self.amount)
+    }
+}
+
+/// How the submarine moves, the position is updated by one command at a time
+pub trait NavigationModel: Default + Clone {
+    fn apply(&mut self, command: &Command) -> error::Result<()>;
+    fn depth(&self) -> i64;
+    fn distance(&self) -> i64;
+
+    /// Only models steering with an aim have one
+    fn aim(&self) -> Option<i64> {
+        None
+    }
 }
 
-struct AimedPosition {
-    depth: i32,
-    distance: i32,
-    aim: i32,
+/// Position of the submarine when up and down directly change the depth
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Position {
+    pub depth: i64,
+    pub distance: i64,
 }
 
-fn parse_line(line: &str) -> Result<Command, &str> {
-    let mut split = line.split(" ");
+/// Position of the submarine when up and down change the aim
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AimedPosition {
+    pub depth: i64,
+    pub distance: i64,
+    pub aim: i64,
+}
+
+/// Parse a command, `hold` may leave out the amount, which can only be 1
+pub fn parse_line(line: &str) -> error::Result<Command> {
+    let (direction, amount) = match line.split_once(' ') {
+        Some((direction, amount)) => (direction, Some(amount)),
+        None => (line, None),
+    };
 
-    let direction = match split.next() {
-        Some("up") => Direction::Up,
-        Some("down") => Direction::Down,
-        Some("forward") => Direction::Forward,
-        _ => return Err("Unknown direction"),
+    let direction = match direction {
+        "up" => Direction::Up,
+        "down" => Direction::Down,
+        "forward" => Direction::Forward,
+        "back" => Direction::Back,
+        "hold" => Direction::Hold,
+        _ => return Err(error::Error::Parse(format!("unknown direction {:?}", direction))),
     };
-    let amount: i32 = match split.next() {
-        Some(val) => val.parse().unwrap_or(0),
-        _ => return Err("Unknown amount"),
+    let amount: i32 = match (direction, amount) {
+        (_, Some(amount)) => amount.parse().or_invalid("invalid amount")?,
+        (Direction::Hold, None) => 1,
+        (_, None) => return Err(error::Error::Parse("expected a direction and an amount".to_string())),
     };
+    ensure(direction != Direction::Hold || amount == 1, "hold is a single step")?;
 
     Ok(Command { direction, amount })
 }
 
-fn parse_input(input: &String) -> Vec<Command> {
-    input.lines().map(parse_line).map(Result::unwrap).collect()
+/// Parse the planned course, one command per line
+pub fn parse(input: &str) -> error::Result<Vec<Command>> {
+    input::parse_lines(input, parse_line)
 }
 
-fn update_position(position: &mut Position, command: &Command) {
+pub fn update_position(position: &mut Position, command: &Command) -> error::Result<()> {
+    let amount = command.amount as i64;
     match command.direction {
-        Direction::Up => position.depth -= command.amount,
-        Direction::Down => position.depth += command.amount,
-        Direction::Forward => position.distance += command.amount,
+        Direction::Up => position.depth = position.depth.checked_sub(amount).or_overflow()?,
+        Direction::Down => position.depth = position.depth.checked_add(amount).or_overflow()?,
+        Direction::Forward => position.distance = position.distance.checked_add(amount).or_overflow()?,
+        Direction::Back => position.distance = position.distance.checked_sub(amount).or_overflow()?,
+        Direction::Hold => {}
     }
+    Ok(())
 }
 
-fn update_aimed_position(position: &mut AimedPosition, command: &Command) {
+pub fn update_aimed_position(position: &mut AimedPosition, command: &Command) -> error::Result<()> {
+    let amount = command.amount as i64;
     match command.direction {
-        Direction::Up => position.aim -= command.amount,
-        Direction::Down => position.aim += command.amount,
-        Direction::Forward => {
-            position.distance += command.amount;
-            position.depth += position.aim * command.amount;
+        Direction::Up => position.aim = position.aim.checked_sub(amount).or_overflow()?,
+        Direction::Down => position.aim = position.aim.checked_add(amount).or_overflow()?,
+        Direction::Forward | Direction::Back => {
+            // going back retraces the way along the aim
+            let amount = if command.direction == Direction::Back { amount.checked_neg().or_overflow()? } else { amount };
+            position.distance = position.distance.checked_add(amount).or_overflow()?;
+            let change = position.aim.checked_mul(amount).or_overflow()?;
+            position.depth = position.depth.checked_add(change).or_overflow()?;
         }
+        Direction::Hold => {}
     }
+    Ok(())
 }
 
-fn part1(commands: &Vec<Command>) -> i32 {
-    let mut position = Position {
-        depth: 0,
-        distance: 0,
-    };
-    for command in commands {
-        update_position(&mut position, &command);
+impl NavigationModel for Position {
+    fn apply(&mut self, command: &Command) -> error::Result<()> {
+        update_position(self, command)
+    }
+
+    fn depth(&self) -> i64 {
+        self.depth
+    }
+
+    fn distance(&self) -> i64 {
+        self.distance
     }
-    position.depth * position.distance
 }
 
-fn part2(commands: &Vec<Command>) -> i32 {
-    let mut position = AimedPosition {
-        depth: 0,
-        distance: 0,
-        aim: 0,
-    };
+impl NavigationModel for AimedPosition {
+    fn apply(&mut self, command: &Command) -> error::Result<()> {
+        update_aimed_position(self, command)
+    }
+
+    fn depth(&self) -> i64 {
+        self.depth
+    }
+
+    fn distance(&self) -> i64 {
+        self.distance
+    }
+
+    fn aim(&self) -> Option<i64> {
+        Some(self.aim)
+    }
+}
+
+/// The position after following the whole course
+pub fn navigate<M: NavigationModel>(commands: &[Command]) -> error::Result<M> {
+    let mut position = M::default();
     for command in commands {
-        update_aimed_position(&mut position, &command);
+        position.apply(command)?;
+    }
+    Ok(position)
+}
+
+/// The position after every command of the course
+pub fn trajectory<M: NavigationModel>(commands: &[Command]) -> error::Result<Vec<M>> {
+    let mut position = M::default();
+    commands
+        .iter()
+        .map(|command| {
+            position.apply(command)?;
+            Ok(position.clone())
+        })
+        .collect()
+}
+
+/// The position after following the first n_steps commands of the course
+pub fn replay<M: NavigationModel>(commands: &[Command], n_steps: usize) -> error::Result<M> {
+    navigate(&commands[..n_steps.min(commands.len())])
+}
+
+fn optional(value: Option<i64>) -> String {
+    value.map_or(String::new(), |v| v.to_string())
+}
+
+/// The first n_steps commands of the course as csv, with the position after each of them
+pub fn course_csv<M: NavigationModel>(commands: &[Command], n_steps: usize) -> error::Result<String> {
+    let commands = &commands[..n_steps.min(commands.len())];
+    let mut csv = String::from("step,command,depth,distance,aim\n");
+
+    for (step, (command, position)) in commands.iter().zip(trajectory::<M>(commands)?).enumerate() {
+        csv += &format!("{},{},{},{},{}\n", step + 1, command, position.depth(), position.distance(), optional(position.aim()));
+    }
+    Ok(csv)
+}
+
+/// The tracks of the simple and the aimed model next to each other
+pub fn compare_tracks(commands: &[Command], n_steps: usize) -> error::Result<String> {
+    let commands = &commands[..n_steps.min(commands.len())];
+    let simple = trajectory::<Position>(commands)?;
+    let aimed = trajectory::<AimedPosition>(commands)?;
+
+    let mut table = format!("{:>20} | {:^25} | {:^34}\n", "", "simple", "aimed");
+    table += &format!("{:>6}  {:<12} | {:>12} {:>12} | {:>12} {:>12} {:>8}\n", "step", "command", "depth", "distance", "depth", "distance", "aim");
+    for (step, command) in commands.iter().enumerate() {
+        let (s, a) = (&simple[step], &aimed[step]);
+        table += &format!(
+            "{:>6}  {:<12} | {:>12} {:>12} | {:>12} {:>12} {:>8}\n",
+            step + 1,
+            command.to_string(),
+            s.depth,
+            s.distance,
+            a.depth,
+            a.distance,
+            a.aim
+        );
     }
-    position.depth * position.distance
+    Ok(table)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    Simple,
+    Aimed,
+}
+
+/// What to show of the course
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    Csv(Model),
+    /// The positions of both models after the last step
+    Replay,
+    Compare,
 }
 
-pub fn day2() {
-    let input = input::get_input(2);
-    let commands = parse_input(&input);
+/// Show the first n_steps commands of the course, all of them if None
+pub fn course(view: View, n_steps: Option<usize>) -> error::Result<()> {
+    let commands = parse(&input::get_input(2))?;
+    let n_steps = n_steps.unwrap_or(commands.len());
 
-    println!("Part1 {}", part1(&commands));
-    println!("Part2 {}", part2(&commands));
+    match view {
+        View::Csv(Model::Simple) => print!("{}", course_csv::<Position>(&commands, n_steps)?),
+        View::Csv(Model::Aimed) => print!("{}", course_csv::<AimedPosition>(&commands, n_steps)?),
+        View::Compare => print!("{}", compare_tracks(&commands, n_steps)?),
+        View::Replay => {
+            let n_steps = n_steps.min(commands.len());
+            if n_steps > 0 {
+                println!("Step {}: {}", n_steps, commands[n_steps - 1]);
+            }
+            let simple = replay::<Position>(&commands, n_steps)?;
+            let aimed = replay::<AimedPosition>(&commands, n_steps)?;
+            println!("Simple: depth {}, distance {}", simple.depth, simple.distance);
+            println!("Aimed: depth {}, distance {}, aim {}", aimed.depth, aimed.distance, aimed.aim);
+        }
+    }
+    Ok(())
+}
+
+// The product of two i64 always fits into an i128
+fn product(position: &impl NavigationModel) -> i128 {
+    position.depth() as i128 * position.distance() as i128
+}
+
+/// Product of depth and distance after following the course
+pub fn part1(commands: &[Command]) -> error::Result<i128> {
+    Ok(product(&navigate::<Position>(commands)?))
+}
+
+/// Product of depth and distance after following the course using the aim
+pub fn part2(commands: &[Command]) -> error::Result<i128> {
+    Ok(product(&navigate::<AimedPosition>(commands)?))
+}
+
+/// Solve both parts in a single pass over the course, in constant memory
+pub fn solve_reader(reader: impl BufRead) -> error::Result<(error::Result<i128>, error::Result<i128>)> {
+    let mut position = Position::default();
+    let mut aimed_position = AimedPosition::default();
+
+    let mut simple = Ok(());
+    let mut aimed = Ok(());
+
+    input::for_each_line(reader, |line| {
+        let command = parse_line(line)?;
+        // stop updating a position once it overflowed
+        if simple.is_ok() {
+            simple = position.apply(&command);
+        }
+        if aimed.is_ok() {
+            aimed = aimed_position.apply(&command);
+        }
+        Ok(())
+    })?;
+
+    Ok((simple.map(|_| product(&position)), aimed.map(|_| product(&aimed_position))))
+}
+
+pub fn day2() -> error::Result<()> {
+    let (part1, part2) = memory::record("both parts", || solve_reader(input::get_input_reader(2)))?;
+
+    println!("Part1 {}", answer(part1));
+    println!("Part2 {}", answer(part2));
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AimedPosition, Position};
+    use crate::error::Error;
+
+    const EXAMPLE: &str = "forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2\n";
+
+    #[test]
+    fn parts() {
+        let commands = super::parse(EXAMPLE).unwrap();
+        assert_eq!(super::part1(&commands), Ok(150));
+        assert_eq!(super::part2(&commands), Ok(900));
+        assert_eq!(super::solve_reader(EXAMPLE.as_bytes()), Ok((Ok(150), Ok(900))));
+    }
+
+    #[test]
+    fn back_and_hold() {
+        let commands = super::parse("down 2\nforward 5\nhold\nhold 1\nback 2\n").unwrap();
+        assert_eq!(super::navigate::<Position>(&commands), Ok(Position { depth: 2, distance: 3 }));
+        assert_eq!(super::navigate::<AimedPosition>(&commands), Ok(AimedPosition { depth: 6, distance: 3, aim: 2 }));
+    }
+
+    #[test]
+    fn trajectory() {
+        let commands = super::parse(EXAMPLE).unwrap();
+        let track = super::trajectory::<AimedPosition>(&commands).unwrap();
+        assert_eq!(track.len(), 6);
+        assert_eq!(track[2], AimedPosition { depth: 40, distance: 13, aim: 5 });
+        assert_eq!(track.last(), super::navigate::<AimedPosition>(&commands).ok().as_ref());
+    }
+
+    #[test]
+    fn course() {
+        let commands = super::parse(EXAMPLE).unwrap();
+        assert_eq!(super::replay::<AimedPosition>(&commands, 3), Ok(AimedPosition { depth: 40, distance: 13, aim: 5 }));
+        assert_eq!(super::replay::<Position>(&commands, 100), super::navigate::<Position>(&commands));
+
+        assert_eq!(
+            super::course_csv::<AimedPosition>(&commands, 2).unwrap(),
+            "step,command,depth,distance,aim\n1,forward 5,0,5,0\n2,down 5,0,5,5\n"
+        );
+        assert_eq!(super::course_csv::<Position>(&commands, 1).unwrap(), "step,command,depth,distance,aim\n1,forward 5,0,5,\n");
+        assert_eq!(super::compare_tracks(&commands, 6).unwrap().lines().count(), 8);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(super::parse("forward 5\nsideways 2\n").err(), Some(Error::Parse("line 2: unknown direction \"sideways\"".to_string())));
+        assert!(super::parse("forward\n").is_err());
+        assert!(super::parse("up x\n").is_err());
+        assert_eq!(super::parse("hold 3\n").err(), Some(Error::Parse("line 1: hold is a single step".to_string())));
+    }
 }
@@ -1,125 +1,464 @@
+//! Day 3: Binary Diagnostic
+use crate::error::{self, answer, ensure};
 use crate::input;
+use crate::memory;
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::BufRead;
 
-#[cfg(test)]
-mod test {
-    const TEST_INPUT: &str = "00100
-        11110
-        10110
-        10111
-        10101
-        01111
-        00111
-        11100
-        10000
-        11001
-        00010
-        01010";
+/// A number of any width, stored as 64 bit words with the least significant first.
+/// It is the storage for reports wider than 128 bits and the type of the answers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WideNumber {
+    // without leading zero words, so that equal numbers are stored the same way
+    words: Vec<u64>,
+}
 
-    #[test]
-    fn part1() {
-        let (n_bits, numbers) = super::parse_input(&String::from(TEST_INPUT));
-        assert_eq!(super::part1(n_bits, &numbers), 198)
+impl WideNumber {
+    fn trim(mut self) -> Self {
+        while self.words.last() == Some(&0) {
+            self.words.pop();
+        }
+        self
     }
 
-    #[test]
-    fn oxygen() {
-        let (n_bits, numbers) = super::parse_input(&String::from(TEST_INPUT));
-        assert_eq!(super::find_ozygen_rating(n_bits, &numbers), 23)
+    pub fn bit(&self, i: usize) -> bool {
+        self.words.get(i / 64).is_some_and(|word| word & (1 << (i % 64)) != 0)
+    }
+
+    pub fn set(&mut self, i: usize) {
+        if self.words.len() <= i / 64 {
+            self.words.resize(i / 64 + 1, 0);
+        }
+        self.words[i / 64] |= 1 << (i % 64);
+    }
+
+    pub fn mul(&self, other: &WideNumber) -> WideNumber {
+        let mut words = vec![0u64; self.words.len() + other.words.len()];
+        for (i, a) in self.words.iter().enumerate() {
+            let mut carry = 0u128;
+            for (j, b) in other.words.iter().enumerate() {
+                let product = *a as u128 * *b as u128 + words[i + j] as u128 + carry;
+                words[i + j] = product as u64;
+                carry = product >> 64;
+            }
+            words[i + other.words.len()] = carry as u64;
+        }
+        WideNumber { words }.trim()
+    }
+
+    // Divide in place, returns the remainder
+    fn div_rem(&mut self, divisor: u64) -> u64 {
+        let mut remainder = 0u128;
+        for word in self.words.iter_mut().rev() {
+            let value = (remainder << 64) | *word as u128;
+            *word = (value / divisor as u128) as u64;
+            remainder = value % divisor as u128;
+        }
+        *self = std::mem::take(self).trim();
+        remainder as u64
     }
 }
 
-fn parse_input(input: &String) -> (usize, Vec<u16>) {
-    let numbers = input
-        .trim()
-        .lines()
-        .map(|line: &str| u16::from_str_radix(line.trim(), 2))
-        .map(Result::unwrap)
-        .collect();
-    let n_bits = input.trim().lines().next().unwrap().trim().len();
+// Without leading zero words, a longer number is larger
+impl Ord for WideNumber {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.words.len().cmp(&other.words.len()).then_with(|| self.words.iter().rev().cmp(other.words.iter().rev()))
+    }
+}
 
-    (n_bits, numbers)
+impl PartialOrd for WideNumber {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
-fn most_common_bit(numbers: &Vec<u16>) -> [u16; 16] {
-    let mut n_ones: [usize; 16] = [0; 16];
-    let mut most_common: [u16; 16] = [0; 16];
+impl From<u128> for WideNumber {
+    fn from(value: u128) -> Self {
+        WideNumber { words: vec![value as u64, (value >> 64) as u64] }.trim()
+    }
+}
 
-    for number in numbers {
-        for bit in 0..16 {
-            if (number & (1 << bit)) > 0 {
-                n_ones[bit] += 1;
-            }
+impl fmt::Display for WideNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // split off 19 decimal digits at a time, the most that fit into a u64
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+        let mut number = self.clone();
+        let mut chunks = vec![number.div_rem(CHUNK)];
+        while !number.words.is_empty() {
+            chunks.push(number.div_rem(CHUNK));
+        }
+
+        write!(f, "{}", chunks.pop().unwrap())?;
+        for chunk in chunks.iter().rev() {
+            write!(f, "{:019}", chunk)?;
         }
+        Ok(())
     }
+}
+
+/// Storage of the numbers in a report, bit i is the i-th least significant one
+pub trait Bits: Clone + Ord {
+    /// A string of 0s and 1s, with the most significant bit first
+    fn from_binary(binary: &[u8]) -> Self;
+    fn bit(&self, i: usize) -> bool;
+    fn to_wide(&self) -> WideNumber;
+}
+
+impl Bits for u64 {
+    fn from_binary(binary: &[u8]) -> Self {
+        binary.iter().fold(0, |number, b| (number << 1) | (b - b'0') as u64)
+    }
+
+    fn bit(&self, i: usize) -> bool {
+        self & (1 << i) != 0
+    }
+
+    fn to_wide(&self) -> WideNumber {
+        WideNumber::from(*self as u128)
+    }
+}
 
-    for bit in 0..16 {
-        let n_zeros = numbers.len() - n_ones[bit];
-        if n_ones[bit] >= n_zeros {
-            most_common[bit] = 1;
+impl Bits for u128 {
+    fn from_binary(binary: &[u8]) -> Self {
+        binary.iter().fold(0, |number, b| (number << 1) | (b - b'0') as u128)
+    }
+
+    fn bit(&self, i: usize) -> bool {
+        self & (1 << i) != 0
+    }
+
+    fn to_wide(&self) -> WideNumber {
+        WideNumber::from(*self)
+    }
+}
+
+impl Bits for WideNumber {
+    fn from_binary(binary: &[u8]) -> Self {
+        let mut number = WideNumber::default();
+        for (i, b) in binary.iter().rev().enumerate() {
+            if *b == b'1' {
+                number.set(i);
+            }
         }
+        number
+    }
+
+    fn bit(&self, i: usize) -> bool {
+        WideNumber::bit(self, i)
+    }
+
+    fn to_wide(&self) -> WideNumber {
+        self.clone()
     }
+}
+
+/// The numbers of a report in the smallest storage they fit into
+pub enum Numbers {
+    U64(Vec<u64>),
+    U128(Vec<u128>),
+    Wide(Vec<WideNumber>),
+}
 
-    most_common
+/// The diagnostic report, all numbers have the same number of bits
+pub struct Report {
+    pub n_bits: usize,
+    pub numbers: Numbers,
 }
 
-fn part1(n_bits: usize, numbers: &Vec<u16>) -> u32 {
-    let most_common = most_common_bit(&numbers);
-    let mut gamma_rate: u16 = 0;
-    for bit in 0..n_bits {
-        gamma_rate += most_common[bit] << bit;
+fn check_binary(line: &str, n_bits: usize) -> error::Result<()> {
+    ensure(line.bytes().all(|b| b == b'0' || b == b'1'), "expected a binary number")?;
+    if line.len() != n_bits {
+        return Err(error::Error::Parse(format!("expected {} bits like the first number, found {}", n_bits, line.len())));
     }
-    let epsilon_rate: u16 = (!gamma_rate) & ((1 << n_bits) - 1);
+    Ok(())
+}
 
-    gamma_rate as u32 * epsilon_rate as u32
+fn parse_numbers<T: Bits>(input: &str, n_bits: usize) -> error::Result<Vec<T>> {
+    input::parse_lines(input, |line| {
+        check_binary(line, n_bits)?;
+        Ok(T::from_binary(line.as_bytes()))
+    })
 }
 
-fn find_ozygen_rating(n_bits: usize, numbers: &Vec<u16>) -> u16 {
-    let mut numbers = numbers.clone();
+/// Parse the report, one binary number per line
+pub fn parse(input: &str) -> error::Result<Report> {
+    let n_bits = input.lines().map(str::trim).find(|line| !line.is_empty()).map_or(0, str::len);
+    ensure(n_bits > 0, "the report is empty")?;
 
-    for bit in (0..n_bits).rev() {
-        let most_common = most_common_bit(&numbers);
+    let numbers = match n_bits {
+        1..=64 => Numbers::U64(parse_numbers(input, n_bits)?),
+        65..=128 => Numbers::U128(parse_numbers(input, n_bits)?),
+        _ => Numbers::Wide(parse_numbers(input, n_bits)?),
+    };
+    Ok(Report { n_bits, numbers })
+}
 
-        numbers = numbers
-            .iter()
-            .filter(|number| (most_common[bit] << bit) == (*number & (1 << bit)))
-            .copied()
-            .collect();
+/// Number of ones of each bit
+pub fn count_ones<T: Bits>(n_bits: usize, numbers: &[T]) -> Vec<usize> {
+    let mut n_ones = vec![0; n_bits];
+    for number in numbers {
+        for (bit, n) in n_ones.iter_mut().enumerate() {
+            if number.bit(bit) {
+                *n += 1;
+            }
+        }
+    }
+    n_ones
+}
 
-        if numbers.len() == 1 {
-            return numbers[0];
+/// The most common value of each bit, true in case of a tie
+pub fn most_common_bit<T: Bits>(n_bits: usize, numbers: &[T]) -> Vec<bool> {
+    count_ones(n_bits, numbers).into_iter().map(|n| 2 * n >= numbers.len()).collect()
+}
+
+// Gamma rate from the most common bits, epsilon rate from the least common ones
+fn power_consumption(most_common: &[bool]) -> WideNumber {
+    let mut gamma_rate = WideNumber::default();
+    let mut epsilon_rate = WideNumber::default();
+    for (bit, common) in most_common.iter().enumerate() {
+        if *common {
+            gamma_rate.set(bit);
+        } else {
+            epsilon_rate.set(bit);
         }
     }
+    gamma_rate.mul(&epsilon_rate)
+}
+
+/// Power consumption, the product of gamma and epsilon rate
+pub fn part1(report: &Report) -> WideNumber {
+    let n_bits = report.n_bits;
+    match &report.numbers {
+        Numbers::U64(numbers) => power_consumption(&most_common_bit(n_bits, numbers)),
+        Numbers::U128(numbers) => power_consumption(&most_common_bit(n_bits, numbers)),
+        Numbers::Wide(numbers) => power_consumption(&most_common_bit(n_bits, numbers)),
+    }
+}
+
+/// Power consumption in a single pass over the report, in memory proportional to the number of bits.
+/// There is no such shortcut for part 2, the ratings need all numbers.
+pub fn part1_reader(reader: impl BufRead) -> error::Result<WideNumber> {
+    let mut n_ones: Vec<usize> = Vec::new();
+    let mut n_numbers = 0;
+
+    input::for_each_line(reader, |line| {
+        if n_ones.is_empty() {
+            n_ones = vec![0; line.len()];
+        }
+        check_binary(line, n_ones.len())?;
+        // the first column is the most significant bit
+        for (n, bit) in n_ones.iter_mut().rev().zip(line.bytes()) {
+            if bit == b'1' {
+                *n += 1;
+            }
+        }
+        n_numbers += 1;
+        Ok(())
+    })?;
+    ensure(n_numbers > 0, "the report is empty")?;
 
-    panic!("Did not find rating");
+    let most_common: Vec<bool> = n_ones.iter().map(|n| 2 * n >= n_numbers).collect();
+    Ok(power_consumption(&most_common))
 }
 
-fn find_carbon_rating(n_bits: usize, numbers: &Vec<u16>) -> u16 {
-    let mut numbers = numbers.clone();
+/// Which numbers are kept at each bit when searching a rating
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Criterion {
+    MostCommon,
+    LeastCommon,
+}
 
-    for bit in (0..n_bits).rev() {
-        let most_common = most_common_bit(&numbers);
+/// How a rating is found, the bit value to keep if zeros and ones are equally common
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RatingRule {
+    pub keep: Criterion,
+    pub on_tie: bool,
+}
 
-        numbers = numbers
-            .iter()
-            .filter(|number| (most_common[bit] << bit) != (*number & (1 << bit)))
-            .copied()
-            .collect();
+/// Oxygen generator rating, keep the most common bit, ones on a tie
+pub const OXYGEN: RatingRule = RatingRule { keep: Criterion::MostCommon, on_tie: true };
 
+/// CO2 scrubber rating, keep the least common bit, zeros on a tie
+pub const CO2: RatingRule = RatingRule { keep: Criterion::LeastCommon, on_tie: false };
+
+/// Find the rating in the sorted numbers. Numbers sharing the bits seen so far form a range,
+/// which the first number with a one splits, so every bit takes a single binary search.
+pub fn find_rating<T: Bits>(n_bits: usize, sorted: &[T], rule: RatingRule) -> error::Result<T> {
+    ensure(!sorted.is_empty(), "the report is empty")?;
+    let mut numbers = sorted;
+
+    for bit in (0..n_bits).rev() {
         if numbers.len() == 1 {
-            return numbers[0];
+            break;
         }
+
+        let split = numbers.partition_point(|number| !number.bit(bit));
+        let (zeros, ones) = numbers.split_at(split);
+        let keep_ones = match (ones.len().cmp(&zeros.len()), rule.keep) {
+            (Ordering::Equal, _) => rule.on_tie,
+            (order, Criterion::MostCommon) => order == Ordering::Greater,
+            (order, Criterion::LeastCommon) => order == Ordering::Less,
+        };
+
+        numbers = if keep_ones { ones } else { zeros };
+        if numbers.is_empty() {
+            return Err(error::Error::Parse(format!("no number left for the rating, all numbers have the same bit {}", bit)));
+        }
+    }
+
+    match numbers {
+        [rating] => Ok(rating.clone()),
+        _ => Err(error::Error::Parse(format!("no unique rating, {} numbers are equal", numbers.len()))),
     }
+}
 
-    panic!("Did not find rating");
+/// Oxygen generator and CO2 scrubber rating following the given rules
+pub fn ratings<T: Bits>(n_bits: usize, numbers: &[T], oxygen: RatingRule, co2: RatingRule) -> error::Result<(T, T)> {
+    let mut sorted = numbers.to_vec();
+    sorted.sort_unstable();
+    Ok((find_rating(n_bits, &sorted, oxygen)?, find_rating(n_bits, &sorted, co2)?))
 }
 
-fn part2(n_bits: usize, numbers: &Vec<u16>) -> i64 {
-    find_carbon_rating(n_bits, &numbers) as i64 * find_ozygen_rating(n_bits, &numbers) as i64
+fn life_support_rating<T: Bits>(n_bits: usize, numbers: &[T]) -> error::Result<WideNumber> {
+    let (oxygen, co2) = ratings(n_bits, numbers, OXYGEN, CO2)?;
+    Ok(oxygen.to_wide().mul(&co2.to_wide()))
 }
 
-pub fn day3() {
+/// Life support rating, the product of oxygen generator and CO2 scrubber rating
+pub fn part2(report: &Report) -> error::Result<WideNumber> {
+    let n_bits = report.n_bits;
+    match &report.numbers {
+        Numbers::U64(numbers) => life_support_rating(n_bits, numbers),
+        Numbers::U128(numbers) => life_support_rating(n_bits, numbers),
+        Numbers::Wide(numbers) => life_support_rating(n_bits, numbers),
+    }
+}
+
+pub fn day3() -> error::Result<()> {
     let input = input::get_input(3);
-    let (n_bits, numbers) = parse_input(&input);
-    println!("Part1: {}", part1(n_bits, &numbers));
-    println!("Part2: {}", part2(n_bits, &numbers));
+    let report = memory::record("parse", || parse(&input))?;
+    println!("Part1: {}", memory::record("part1", || part1(&report)));
+    println!("Part2: {}", answer(memory::record("part2", || part2(&report))));
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Numbers, RatingRule, WideNumber, CO2, OXYGEN};
+
+    const TEST_INPUT: &str = "00100
+        11110
+        10110
+        10111
+        10101
+        01111
+        00111
+        11100
+        10000
+        11001
+        00010
+        01010";
+
+    #[test]
+    fn part1() {
+        let report = super::parse(TEST_INPUT).unwrap();
+        assert_eq!(super::part1(&report).to_string(), "198")
+    }
+
+    #[test]
+    fn part1_reader() {
+        assert_eq!(super::part1_reader(TEST_INPUT.as_bytes()).unwrap().to_string(), "198")
+    }
+
+    fn example_numbers() -> Vec<u64> {
+        match super::parse(TEST_INPUT).unwrap().numbers {
+            Numbers::U64(numbers) => numbers,
+            _ => panic!("5 bit numbers should be stored as u64"),
+        }
+    }
+
+    #[test]
+    fn ratings() {
+        assert_eq!(super::ratings(5, &example_numbers(), OXYGEN, CO2), Ok((23, 10)));
+        assert_eq!(super::part2(&super::parse(TEST_INPUT).unwrap()).unwrap().to_string(), "230");
+    }
+
+    #[test]
+    fn tie_breaking() {
+        // 10110 and 10111 are left for the last bit
+        let zeros_on_tie = RatingRule { on_tie: false, ..OXYGEN };
+        assert_eq!(super::ratings(5, &example_numbers(), zeros_on_tie, CO2), Ok((22, 10)));
+
+        // 01111 and 01010 tie at the third bit, 01111 is the only one left keeping ones
+        let ones_on_tie = RatingRule { on_tie: true, ..CO2 };
+        assert_eq!(super::ratings(5, &example_numbers(), OXYGEN, ones_on_tie), Ok((23, 15)));
+    }
+
+    #[test]
+    fn no_unique_rating() {
+        assert!(super::ratings(2, &[0b01u64, 0b01], OXYGEN, CO2).is_err());
+        // all numbers have the same first bit, none has the least common one
+        assert!(super::ratings(3, &[0b010u64, 0b011], OXYGEN, CO2).is_err());
+        assert!(super::ratings::<u64>(3, &[], OXYGEN, CO2).is_err());
+        assert_eq!(super::ratings(3, &[0b101u64], OXYGEN, CO2), Ok((0b101, 0b101)));
+    }
+
+    // The example with n_zeros zeros appended to every number, which shifts both ratings
+    fn widened(n_zeros: usize) -> String {
+        TEST_INPUT.lines().map(|line| format!("{}{}\n", line.trim(), "0".repeat(n_zeros))).collect()
+    }
+
+    // The bits of the low number shifted left, with the lowest shift bits set to the fill value
+    fn shifted(low: u128, shift: usize, fill: bool) -> WideNumber {
+        let mut number = WideNumber::default();
+        for bit in 0..shift + 5 {
+            let set = if bit < shift { fill } else { low & (1 << (bit - shift)) != 0 };
+            if set {
+                number.set(bit);
+            }
+        }
+        number
+    }
+
+    #[test]
+    fn wide() {
+        for (n_zeros, storage) in [(55, "u64"), (95, "u128"), (145, "wide")] {
+            let input = widened(n_zeros);
+            let report = super::parse(&input).unwrap();
+            let found = match report.numbers {
+                Numbers::U64(_) => "u64",
+                Numbers::U128(_) => "u128",
+                Numbers::Wide(_) => "wide",
+            };
+            assert_eq!(found, storage);
+
+            // zero is the most common value of the appended bits, so they are ones in the epsilon rate
+            let power = shifted(22, n_zeros, false).mul(&shifted(9, n_zeros, true));
+            assert_eq!(super::part1(&report), power);
+            assert_eq!(super::part1_reader(input.as_bytes()), Ok(power));
+            assert_eq!(super::part2(&report), Ok(shifted(23, n_zeros, false).mul(&shifted(10, n_zeros, false))));
+        }
+    }
+
+    #[test]
+    fn wide_number() {
+        let a = WideNumber::from(u128::MAX);
+        assert_eq!(a.to_string(), u128::MAX.to_string());
+        assert_eq!(a.mul(&WideNumber::from(1)), a);
+        assert_eq!(a.mul(&WideNumber::default()).to_string(), "0");
+        assert_eq!(
+            a.mul(&a).to_string(),
+            "115792089237316195423570985008687907852589419931798687112530834793049593217025"
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(super::parse("0101\n011").is_err());
+        assert!(super::parse("0102").is_err());
+        assert!(super::parse("\n\n").is_err());
+        assert!(super::part1_reader("0101\n011".as_bytes()).is_err());
+        assert!(super::part1_reader("".as_bytes()).is_err());
+    }
 }
@@ -1,134 +1,537 @@
+//! Day 4: Giant Squid
+use crate::algorithms::{self, Algorithm};
+use crate::error::{self, ensure, OrInvalid};
+use crate::fxhash::FxHashMap;
 use crate::input;
-use std::collections::VecDeque;
+use crate::memory;
+use std::collections::{HashSet, VecDeque};
 use std::time::Instant;
 
-#[derive(Debug, Clone, Copy)]
-struct Board {
-    numbers: [[u8; 5]; 5],
-    marked: [[bool; 5]; 5],
+/// A way to complete a board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+    Rows,
+    Columns,
+    /// Either diagonal, only square boards have them
+    Diagonals,
+    FourCorners,
+    FullCard,
+}
+
+/// The puzzle only counts full rows and columns
+pub const DEFAULT_PATTERNS: [Pattern; 2] = [Pattern::Rows, Pattern::Columns];
+
+/// A bingo board of any size and which of its numbers have been called
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Board {
+    pub n_rows: usize,
+    pub n_cols: usize,
+    /// The numbers row by row
+    pub numbers: Vec<u32>,
+    pub marked: Vec<bool>,
+}
+
+/// The numbers in the order they are called, all bingo boards and the patterns that win
+pub struct Game {
+    pub numbers: Vec<u32>,
+    pub boards: Vec<Board>,
+    pub patterns: Vec<Pattern>,
 }
 
 impl Board {
-    pub fn new() -> Self {
-        Self {
-            numbers: [[0u8; 5]; 5],
-            marked: [[false; 5]; 5],
+    /// A board with nothing marked from its rows of numbers
+    pub fn new(rows: Vec<Vec<u32>>) -> error::Result<Self> {
+        let n_rows = rows.len();
+        let n_cols = rows.first().map_or(0, Vec::len);
+        ensure(n_cols > 0, "a board needs at least one number")?;
+        ensure(rows.iter().all(|row| row.len() == n_cols), "all rows of a board must have the same length")?;
+
+        let numbers: Vec<u32> = rows.into_iter().flatten().collect();
+        let mut seen = HashSet::new();
+        if let Some(number) = numbers.iter().find(|n| !seen.insert(**n)) {
+            return Err(error::Error::Parse(format!("{} is on the board twice", number)));
         }
+
+        Ok(Self{n_rows, n_cols, marked: vec![false; numbers.len()], numbers})
     }
 
-    pub fn finished(&self) -> bool {
-        for row in 0..5 {
-            if self.marked[row].iter().all(|m| *m) {
-                return true;
-            }
-        }
+    /// The cells, row by row, of every line that completes one of the patterns.
+    /// For searching draw orders, `finished` checks the patterns without allocating.
+    pub fn lines(&self, patterns: &[Pattern]) -> Vec<Vec<usize>> {
+        let (n_rows, n_cols) = (self.n_rows, self.n_cols);
+        let cell = |row: usize, col: usize| row * n_cols + col;
+        let mut lines = Vec::new();
 
-        'col: for col in 0..5 {
-            for row in 0..5 {
-                if !self.marked[row][col] {
-                    continue 'col;
+        for pattern in patterns {
+            match pattern {
+                Pattern::Rows => lines.extend((0..n_rows).map(|row| (0..n_cols).map(|col| cell(row, col)).collect())),
+                Pattern::Columns => lines.extend((0..n_cols).map(|col| (0..n_rows).map(|row| cell(row, col)).collect())),
+                Pattern::Diagonals if n_rows == n_cols => {
+                    lines.push((0..n_rows).map(|i| cell(i, i)).collect());
+                    lines.push((0..n_rows).map(|i| cell(i, n_cols - 1 - i)).collect());
                 }
+                Pattern::Diagonals => {}
+                Pattern::FourCorners => {
+                    let mut corners = vec![cell(0, 0), cell(0, n_cols - 1), cell(n_rows - 1, 0), cell(n_rows - 1, n_cols - 1)];
+                    corners.sort_unstable();
+                    corners.dedup();
+                    lines.push(corners);
+                }
+                Pattern::FullCard => lines.push((0..self.numbers.len()).collect()),
             }
-            return true;
         }
+        lines
+    }
 
-        false
+    fn is_marked(&self, row: usize, col: usize) -> bool {
+        self.marked[row * self.n_cols + col]
     }
 
-    pub fn score(&self) -> u32 {
-        let mut sum = 0u32;
-        for row in 0..5 {
-            for col in 0..5 {
-                if !self.marked[row][col] {
-                    sum += self.numbers[row][col] as u32;
-                }
+    fn completes(&self, pattern: Pattern) -> bool {
+        let (n_rows, n_cols) = (self.n_rows, self.n_cols);
+        match pattern {
+            Pattern::Rows => (0..n_rows).any(|row| (0..n_cols).all(|col| self.is_marked(row, col))),
+            Pattern::Columns => (0..n_cols).any(|col| (0..n_rows).all(|row| self.is_marked(row, col))),
+            Pattern::Diagonals => {
+                n_rows == n_cols
+                    && ((0..n_rows).all(|i| self.is_marked(i, i))
+                        || (0..n_rows).all(|i| self.is_marked(i, n_cols - 1 - i)))
             }
+            Pattern::FourCorners => [(0, 0), (0, n_cols - 1), (n_rows - 1, 0), (n_rows - 1, n_cols - 1)]
+                .iter()
+                .all(|&(row, col)| self.is_marked(row, col)),
+            Pattern::FullCard => self.marked.iter().all(|m| *m),
         }
-        sum
     }
 
-    pub fn mark(&mut self, number: u8) -> bool {
-        for row in 0..5 {
-            match self.numbers[row].iter().position(|n| *n == number) {
-                Some(n) => {
-                    self.marked[row][n] = true;
-                    return true;
-                }
-                None => {}
+    /// Whether any of the patterns is completely marked
+    pub fn finished(&self, patterns: &[Pattern]) -> bool {
+        patterns.iter().any(|pattern| self.completes(*pattern))
+    }
+
+    /// Sum of all unmarked numbers
+    pub fn unmarked_sum(&self) -> u64 {
+        self.numbers
+            .iter()
+            .zip(&self.marked)
+            .filter(|(_, marked)| !**marked)
+            .map(|(n, _)| *n as u64)
+            .sum()
+    }
+
+    /// Unmarked sum times the last called number, which always fits into a u128
+    pub fn score(&self, number: u32) -> u128 {
+        self.unmarked_sum() as u128 * number as u128
+    }
+
+    /// Mark the number, returns whether it is on the board
+    pub fn mark(&mut self, number: u32) -> bool {
+        match self.numbers.iter().position(|n| *n == number) {
+            Some(i) => {
+                self.marked[i] = true;
+                true
             }
+            None => false,
         }
-
-        false
     }
 }
 
-fn parse_input(input: &String) -> (Vec<u8>, Vec<Board>) {
-    let mut boards = Vec::new();
+fn parse_board(group: &str) -> error::Result<Board> {
+    let rows = input::parse_lines(group, |line| {
+        line.split_ascii_whitespace()
+            .map(|n| n.parse::<u32>().or_invalid("invalid board number"))
+            .collect::<error::Result<Vec<_>>>()
+    })?;
+    Board::new(rows)
+}
 
+/// Parse the called numbers and the boards, separated by empty lines.
+/// Boards can have any size, full rows and columns win.
+pub fn parse(input: &str) -> error::Result<Game> {
     let mut groups = input.split("\n\n");
 
-    let numbers: Vec<u8> = groups
+    let numbers: Vec<u32> = groups
         .next()
-        .unwrap()
+        .unwrap_or("")
+        .trim()
         .split(",")
-        .map(str::parse::<u8>)
-        .map(Result::unwrap)
-        .collect();
+        .map(|n| n.parse::<u32>().or_invalid("invalid called number"))
+        .collect::<error::Result<_>>()?;
 
-    for group in groups {
-        let mut board = Board::new();
-        group
-            .split_ascii_whitespace()
-            .map(str::parse::<u8>)
-            .map(Result::unwrap)
-            .enumerate()
-            .for_each(|el: (usize, u8)| board.numbers[el.0 / 5][el.0 % 5] = el.1);
+    let boards = groups
+        .filter(|g| !g.trim().is_empty())
+        .enumerate()
+        .map(|(i, group)| {
+            parse_board(group).map_err(|err| match err {
+                error::Error::Parse(msg) => error::Error::Parse(format!("board {}: {}", i + 1, msg)),
+                err => err,
+            })
+        })
+        .collect::<error::Result<_>>()?;
+
+    Ok(Game{numbers, boards, patterns: DEFAULT_PATTERNS.to_vec()})
+}
+
+/// A board completing one of the patterns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Win {
+    /// Index of the board in the game
+    pub board: usize,
+    /// Index of the called number that completed the board
+    pub turn: usize,
+    pub number: u32,
+    pub unmarked_sum: u64,
+    pub score: u128,
+}
+
+/// All wins of a game in the order they happened and the boards that never won
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Timeline {
+    pub wins: Vec<Win>,
+    pub never_won: Vec<usize>,
+}
 
-        boards.push(board);
+impl Timeline {
+    /// The k-th board to win, counting from 0.
+    /// Boards completed by the same number win in the order of the game.
+    pub fn winner(&self, k: usize) -> Option<&Win> {
+        self.wins.get(k)
     }
 
-    (numbers, boards)
+    pub fn first(&self) -> Option<&Win> {
+        self.wins.first()
+    }
+
+    pub fn last(&self) -> Option<&Win> {
+        self.wins.last()
+    }
 }
 
-fn part1(numbers: &Vec<u8>, boards: &Vec<Board>) -> u32 {
-    let mut boards = boards.clone();
+/// Where a number is on the boards, as (board, row, col)
+pub type Index = FxHashMap<u32, Vec<(usize, usize, usize)>>;
 
-    for number in numbers {
-        for board in boards.iter_mut() {
-            if board.mark(*number) {
-                if board.finished() {
-                    return board.score() * *number as u32;
-                }
+/// Find every number on all boards, in the order of the boards
+pub fn index(boards: &[Board]) -> Index {
+    let mut index = Index::default();
+    for (i, board) in boards.iter().enumerate() {
+        for (j, number) in board.numbers.iter().enumerate() {
+            index.entry(*number).or_default().push((i, j / board.n_cols, j % board.n_cols));
+        }
+    }
+    index
+}
+
+/// How many numbers of each line of a board are marked
+#[derive(Debug, Clone)]
+struct Hits {
+    marked: Vec<bool>,
+    rows: Vec<usize>,
+    cols: Vec<usize>,
+    diagonals: [usize; 2],
+    corners: usize,
+    total: usize,
+    unmarked_sum: u64,
+    complete: bool,
+    won: bool,
+}
+
+impl Hits {
+    // Count the cells already marked, a board may even be complete before the first call
+    fn new(board: &Board, patterns: &[Pattern]) -> Self {
+        let mut hits = Self{
+            marked: vec![false; board.numbers.len()],
+            rows: vec![0; board.n_rows],
+            cols: vec![0; board.n_cols],
+            diagonals: [0; 2],
+            corners: 0,
+            total: 0,
+            unmarked_sum: board.numbers.iter().map(|n| *n as u64).sum(),
+            complete: board.finished(patterns),
+            won: false,
+        };
+        for (cell, _) in board.marked.iter().enumerate().filter(|(_, marked)| **marked) {
+            hits.count(board, cell / board.n_cols, cell % board.n_cols, patterns);
+        }
+        hits
+    }
+
+    // Mark the cell and return whether this completed one of the patterns going through it,
+    // the only ones that can have been completed by it
+    fn count(&mut self, board: &Board, row: usize, col: usize, patterns: &[Pattern]) -> bool {
+        let cell = row * board.n_cols + col;
+        if self.marked[cell] {
+            return false;
+        }
+        self.marked[cell] = true;
+        self.unmarked_sum -= board.numbers[cell] as u64;
+        self.rows[row] += 1;
+        self.cols[col] += 1;
+        self.total += 1;
+
+        let square = board.n_rows == board.n_cols;
+        let main_diagonal = square && row == col;
+        let anti_diagonal = square && row + col == board.n_cols - 1;
+        if main_diagonal {
+            self.diagonals[0] += 1;
+        }
+        if anti_diagonal {
+            self.diagonals[1] += 1;
+        }
+        let corner = is_corner(board, row, col);
+        if corner {
+            self.corners += 1;
+        }
+
+        patterns.iter().any(|pattern| match pattern {
+            Pattern::Rows => self.rows[row] == board.n_cols,
+            Pattern::Columns => self.cols[col] == board.n_rows,
+            Pattern::Diagonals => {
+                (main_diagonal && self.diagonals[0] == board.n_rows) || (anti_diagonal && self.diagonals[1] == board.n_rows)
+            }
+            // boards with a single row or column have less than four distinct corners
+            Pattern::FourCorners => corner && self.corners == board.n_rows.min(2) * board.n_cols.min(2),
+            Pattern::FullCard => self.total == board.numbers.len(),
+        })
+    }
+}
+
+fn is_corner(board: &Board, row: usize, col: usize) -> bool {
+    (row == 0 || row == board.n_rows - 1) && (col == 0 || col == board.n_cols - 1)
+}
+
+/// Mark one number, like `Board::mark` and `Board::finished` returns whether
+/// the board is complete after a number on it was called
+fn mark_hit(board: &Board, hits: &mut Hits, row: usize, col: usize, patterns: &[Pattern]) -> bool {
+    if hits.count(board, row, col, patterns) {
+        hits.complete = true;
+    }
+    hits.complete
+}
+
+/// Call all numbers and record every board when it wins.
+/// Each call only visits the boards containing the number.
+pub fn simulate(game: &Game) -> Timeline {
+    let index = index(&game.boards);
+    let mut hits: Vec<Hits> = game.boards.iter().map(|board| Hits::new(board, &game.patterns)).collect();
+    let mut wins = Vec::new();
+
+    for (turn, number) in game.numbers.iter().enumerate() {
+        if wins.len() == game.boards.len() {
+            break;
+        }
+        for &(i, row, col) in index.get(number).into_iter().flatten() {
+            let (board, hits) = (&game.boards[i], &mut hits[i]);
+            if !hits.won && mark_hit(board, hits, row, col, &game.patterns) {
+                hits.won = true;
+                let unmarked_sum = hits.unmarked_sum;
+                let score = unmarked_sum as u128 * *number as u128;
+                wins.push(Win{board: i, turn, number: *number, unmarked_sum, score});
             }
         }
     }
-    0
+
+    let never_won = (0..hits.len()).filter(|i| !hits[*i].won).collect();
+    Timeline{wins, never_won}
 }
 
-fn part2(numbers: &Vec<u8>, boards: &Vec<Board>) -> u32 {
-    let mut boards: VecDeque<Board> = boards.iter().copied().collect();
+/// Call all numbers, marking and checking every board that has not won yet
+pub fn simulate_scan(game: &Game) -> Timeline {
+    let mut boards: VecDeque<(usize, Board)> = game.boards.iter().cloned().enumerate().collect();
+    let mut wins = Vec::new();
 
-    for number in numbers {
+    for (turn, number) in game.numbers.iter().enumerate() {
+        if boards.is_empty() {
+            break;
+        }
         for _ in 0..boards.len() {
-            let mut board = boards.pop_front().unwrap();
-            if board.mark(*number) && board.finished() {
-                if boards.len() == 0 {
-                    return board.score() * *number as u32;
-                }
+            let (board, mut state) = boards.pop_front().unwrap();
+            if state.mark(*number) && state.finished(&game.patterns) {
+                let unmarked_sum = state.unmarked_sum();
+                wins.push(Win{board, turn, number: *number, unmarked_sum, score: state.score(*number)});
             } else {
-                boards.push_back(board);
+                boards.push_back((board, state));
             }
         }
     }
-    0
+
+    Timeline{wins, never_won: boards.into_iter().map(|(board, _)| board).collect()}
+}
+
+/// Final score of the first board to win
+pub fn part1(game: &Game) -> u128 {
+    simulate(game).first().map_or(0, |win| win.score)
+}
+
+/// Final score of the last board to win
+pub fn part2(game: &Game) -> u128 {
+    simulate(game).last().map_or(0, |win| win.score)
+}
+
+pub fn part1_scan(game: &Game) -> u128 {
+    simulate_scan(game).first().map_or(0, |win| win.score)
+}
+
+pub fn part2_scan(game: &Game) -> u128 {
+    simulate_scan(game).last().map_or(0, |win| win.score)
 }
 
-pub fn day4() {
+/// The number index and the scan over all boards
+pub const ALGORITHMS: &[Algorithm<Game, u128>] = &[
+    Algorithm { name: "indexed", part1, part2 },
+    Algorithm { name: "scan", part1: part1_scan, part2: part2_scan },
+];
+
+pub fn day4() -> error::Result<()> {
     let input = input::get_input(4);
-    let (numbers, boards) = parse_input(&input);
+    let game = memory::record("parse", || parse(&input))?;
+
+    let algorithm = algorithms::select(ALGORITHMS)?;
 
     let t0 = Instant::now();
-    println!("Part1: {}", part1(&numbers, &boards));
-    println!("Part2: {}", part2(&numbers, &boards));
+    println!("Part1: {}", memory::record("part1", || (algorithm.part1)(&game)));
+    println!("Part2: {}", memory::record("part2", || (algorithm.part2)(&game)));
     println!("Time: {} us", t0.elapsed().as_micros());
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Board, Pattern};
+
+    const EXAMPLE: &str = "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7
+";
+
+    #[test]
+    fn parts() {
+        let game = super::parse(EXAMPLE).unwrap();
+        assert_eq!(super::part1(&game), 4512);
+        assert_eq!(super::part2(&game), 1924);
+    }
+
+    #[test]
+    fn timeline() {
+        let timeline = super::simulate(&super::parse(EXAMPLE).unwrap());
+        let boards: Vec<_> = timeline.wins.iter().map(|win| win.board).collect();
+        assert_eq!(boards, vec![2, 0, 1]);
+        assert!(timeline.never_won.is_empty());
+
+        let first = timeline.winner(0).unwrap();
+        assert_eq!((first.turn, first.number, first.unmarked_sum, first.score), (11, 24, 188, 4512));
+        assert_eq!(timeline.winner(1).map(|win| win.number), Some(16));
+        assert_eq!(timeline.last().map(|win| (win.unmarked_sum, win.number)), Some((148, 13)));
+        assert_eq!(timeline.winner(3), None);
+
+        let game = super::parse("1,2\n\n1 2\n\n3 4\n\n2 5\n").unwrap();
+        let timeline = super::simulate(&game);
+        assert_eq!(timeline.wins.iter().map(|win| (win.board, win.turn)).collect::<Vec<_>>(), vec![(0, 0), (2, 1)]);
+        assert_eq!(timeline.never_won, vec![1]);
+    }
+
+    #[test]
+    fn indexed_patterns() {
+        let input = "5,1,9,3,7,2,4,6,8,1\n\n1 2 3\n4 5 6\n7 8 9\n\n1 2\n3 4\n5 6\n\n9 5 1 7\n\n3\n";
+        let all = [Pattern::Rows, Pattern::Columns, Pattern::Diagonals, Pattern::FourCorners, Pattern::FullCard];
+        for n in 0..=all.len() {
+            for patterns in [&all[..n], &all[n..]] {
+                let mut game = super::parse(input).unwrap();
+                game.patterns = patterns.to_vec();
+                assert_eq!(super::simulate(&game), super::simulate_scan(&game), "{:?}", patterns);
+            }
+        }
+    }
+
+    fn marked(rows: Vec<Vec<u32>>, numbers: &[u32]) -> Board {
+        let mut board = Board::new(rows).unwrap();
+        for number in numbers {
+            board.mark(*number);
+        }
+        board
+    }
+
+    #[test]
+    fn patterns() {
+        let square = || vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]];
+
+        let board = marked(square(), &[3, 5, 7]);
+        assert!(board.finished(&[Pattern::Diagonals]));
+        assert!(!board.finished(&[Pattern::Rows, Pattern::Columns]));
+
+        let board = marked(square(), &[1, 3, 7, 9]);
+        assert!(board.finished(&[Pattern::FourCorners]));
+        assert!(!board.finished(&[Pattern::Diagonals, Pattern::FullCard]));
+
+        let board = marked(square(), &[1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert!(board.finished(&[Pattern::FullCard]));
+        assert_eq!(board.unmarked_sum(), 0);
+
+        // a 2x4 board has no diagonals
+        let board = marked(vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8]], &[1, 6]);
+        assert!(!board.finished(&[Pattern::Diagonals]));
+        let board = marked(vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8]], &[2, 6]);
+        assert!(board.finished(&[Pattern::Columns]));
+        assert_eq!(board.score(6), (1 + 3 + 4 + 5 + 7 + 8) * 6);
+    }
+
+    #[test]
+    fn marked_before() {
+        let mut game = super::parse("5,1,2,9,3\n\n1 2\n3 4\n\n5 6\n7 8\n\n9 1\n2 3\n").unwrap();
+        game.boards[0].mark(4);
+        game.boards[1].mark(5);
+        game.boards[1].mark(6);
+        game.boards[2].mark(2);
+        let timeline = super::simulate(&game);
+        assert_eq!(timeline, super::simulate_scan(&game));
+        let wins: Vec<_> = timeline.wins.iter().map(|win| (win.board, win.turn)).collect();
+        assert_eq!(wins, vec![(1, 0), (0, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn lines() {
+        let board = marked(vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]], &[1, 3, 5, 7, 9]);
+        let all = [Pattern::Rows, Pattern::Columns, Pattern::Diagonals, Pattern::FourCorners, Pattern::FullCard];
+        for pattern in all {
+            let complete = board.lines(&[pattern]).iter().any(|line| line.iter().all(|cell| board.marked[*cell]));
+            assert_eq!(complete, board.finished(&[pattern]), "{:?}", pattern);
+        }
+    }
+
+    #[test]
+    fn other_sizes() {
+        let game = super::parse("1000,7,300\n\n1000 2\n300 4\n\n7 8 9\n").unwrap();
+        assert_eq!((game.boards[0].n_rows, game.boards[0].n_cols), (2, 2));
+        assert_eq!((game.boards[1].n_rows, game.boards[1].n_cols), (1, 3));
+        // every number of a single row board is a column of its own
+        assert_eq!(super::part1(&game), (8 + 9) * 7);
+        assert_eq!(super::part2(&game), (2 + 4) * 300);
+    }
+
+    #[test]
+    fn malformed_boards() {
+        let error = |input: &str| super::parse(input).err().map(|err| err.to_string());
+        assert_eq!(
+            error("1,2\n\n1 2\n3\n").as_deref(),
+            Some("Invalid input, board 1: all rows of a board must have the same length")
+        );
+        assert_eq!(error("1,2\n\n1 2\n\n3 3\n").as_deref(), Some("Invalid input, board 2: 3 is on the board twice"));
+        assert!(error("1,2\n\n1 x\n").is_some());
+        assert!(error("1,2\n\n1 -2\n").is_some());
+    }
 }
@@ -1,18 +1,25 @@
+//! Day 5: Hydrothermal Venture
+use crate::algorithms::{self, Algorithm};
+use crate::error::{self, answer, ensure, OrInvalid};
+use crate::fxhash::{FxBuildHasher, FxHashMap, FxHashSet};
 use crate::input;
-use std::cmp::{max, min};
+use crate::memory;
+use std::cmp::max;
 use std::collections::HashMap;
+use std::hash::BuildHasher;
 use std::time::Instant;
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
-struct Point {
-    x: u16,
-    y: u16,
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
 }
 
+/// A line of vents from p0 to p1, horizontal, vertical or at 45 degrees
 #[derive(Debug)]
-struct Line {
-    p0: Point,
-    p1: Point,
+pub struct Line {
+    pub p0: Point,
+    pub p1: Point,
 }
 
 impl Line {
@@ -33,111 +40,331 @@ impl Line {
     }
 }
 
-fn parse_point(point: &str) -> Point {
-    let mut iter = point.split(",");
-    let x: u16 = iter.next().unwrap().parse().unwrap();
-    let y: u16 = iter.next().unwrap().parse().unwrap();
+fn parse_point(point: &str) -> error::Result<Point> {
+    let (x, y) = point.split_once(',').or_invalid("expected a point x,y")?;
 
-    Point { x, y }
+    Ok(Point {
+        x: x.trim().parse().or_invalid("invalid x coordinate")?,
+        y: y.trim().parse().or_invalid("invalid y coordinate")?,
+    })
 }
 
-fn parse_line(line: &str) -> Line {
-    let points: Vec<Point> = line.split(" -> ").map(parse_point).collect();
+fn parse_line(line: &str) -> error::Result<Line> {
+    let (p0, p1) = line.split_once(" -> ").or_invalid("did not find 2 points")?;
 
-    if points.len() != 2 {
-        panic!("Did not find 2 lines");
+    let line = Line {
+        p0: parse_point(p0)?,
+        p1: parse_point(p1)?,
+    };
+    ensure(
+        line.is_axis_parallel() || line.p0.x.abs_diff(line.p1.x) == line.p0.y.abs_diff(line.p1.y),
+        "lines must be horizontal, vertical or at 45 degrees",
+    )?;
+    Ok(line)
+}
+
+/// Parse the vent lines, one `x0,y0 -> x1,y1` per line
+pub fn parse(input: &str) -> error::Result<Vec<Line>> {
+    input::parse_lines(input, parse_line)
+}
+
+// Longer lines would take too long and too much memory to draw, the analytic algorithm counts them
+const MAX_RASTER_LENGTH: u64 = 1 << 20;
+
+/// Add one vent for each point covered by the line, counts saturate at u16::MAX
+pub fn add_vents<S: BuildHasher>(vents: &mut HashMap<Point, u16, S>, line: &Line) -> error::Result<()> {
+    // differences of two i64 do not always fit into an i64, the steps and their number do
+    let n = max(line.p0.x.abs_diff(line.p1.x), line.p0.y.abs_diff(line.p1.y));
+    ensure(n < MAX_RASTER_LENGTH, "line is too long to draw, use --algo analytic")?;
+    let step_x = (line.p1.x.cmp(&line.p0.x)) as i64;
+    let step_y = (line.p1.y.cmp(&line.p0.y)) as i64;
+
+    for i in 0..=n as i64 {
+        let point = Point {
+            x: line.p0.x + i * step_x,
+            y: line.p0.y + i * step_y,
+        };
+        match vents.get_mut(&point) {
+            Some(val) => *val = val.saturating_add(1),
+            None => {
+                vents.insert(point, 1);
+            }
+        }
     }
+    Ok(())
+}
 
-    Line {
-        p0: points[0],
-        p1: points[1],
+/// Number of points covered by at least two lines, diagonal lines are skipped unless with_diagonals
+pub fn count_overlaps<S: BuildHasher + Default>(lines: &[Line], with_diagonals: bool) -> error::Result<usize> {
+    let mut vents: HashMap<Point, u16, S> = HashMap::default();
+
+    for line in lines.iter().filter(|l| with_diagonals || l.is_axis_parallel()) {
+        add_vents(&mut vents, line)?;
     }
+
+    Ok(vents.values().filter(|v| **v >= 2).count())
 }
 
-fn parse_input(input: &String) -> Vec<Line> {
-    input.lines().map(parse_line).collect()
+/// Number of points covered by at least two horizontal or vertical lines
+pub fn part1(lines: &[Line]) -> error::Result<usize> {
+    count_overlaps::<FxBuildHasher>(lines, false)
 }
 
-fn add_vents(vents: &mut HashMap<Point, u16>, line: &Line) {
-    if line.is_horizontal() {
-        let y = line.p0.y;
-        let start = min(line.p0.x, line.p1.x);
-        let end = max(line.p0.x, line.p1.x);
+/// Number of points covered by at least two lines
+pub fn part2(lines: &[Line]) -> error::Result<usize> {
+    count_overlaps::<FxBuildHasher>(lines, true)
+}
 
-        for x in start..=end {
-            let point = Point { x, y };
-            match vents.get_mut(&point) {
-                Some(val) => *val += 1,
-                None => {
-                    vents.insert(point, 1);
-                }
-            }
+/// The four directions a line of vents can have
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Direction {
+    Horizontal,
+    Vertical,
+    Diagonal,
+    AntiDiagonal,
+}
+
+// Which of the parallel lines of the direction a point is on, and where on that line,
+// the x coordinate for all but vertical lines. i128 so that sums of two i64 do not overflow.
+fn position(direction: Direction, (x, y): (i128, i128)) -> (i128, i128) {
+    match direction {
+        Direction::Horizontal => (y, x),
+        Direction::Vertical => (x, y),
+        Direction::Diagonal => (x - y, x),
+        Direction::AntiDiagonal => (x + y, x),
+    }
+}
+
+/// A stretch from start to end on the infinite line given by direction and key
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    direction: Direction,
+    key: i128,
+    start: i128,
+    end: i128,
+}
+
+impl Span {
+    // A single point is a horizontal line
+    fn new(line: &Line) -> Self {
+        let (p0, p1) = ((line.p0.x as i128, line.p0.y as i128), (line.p1.x as i128, line.p1.y as i128));
+        let direction = if line.is_horizontal() {
+            Direction::Horizontal
+        } else if line.is_vertical() {
+            Direction::Vertical
+        } else if (p1.0 - p0.0) == (p1.1 - p0.1) {
+            Direction::Diagonal
+        } else {
+            Direction::AntiDiagonal
+        };
+        let (key, t0) = position(direction, p0);
+        let (_, t1) = position(direction, p1);
+        Span{direction, key, start: t0.min(t1), end: t0.max(t1)}
+    }
+
+    fn contains(&self, point: (i128, i128)) -> bool {
+        let (key, t) = position(self.direction, point);
+        key == self.key && (self.start..=self.end).contains(&t)
+    }
+
+    fn x_range(&self) -> (i128, i128) {
+        match self.direction {
+            Direction::Vertical => (self.key, self.key),
+            _ => (self.start, self.end),
         }
-    } else if line.is_vertical() {
-        let x = line.p0.x;
-        let start = min(line.p0.y, line.p1.y);
-        let end = max(line.p0.y, line.p1.y);
-
-        for y in start..=end {
-            let point = Point { x, y };
-            match vents.get_mut(&point) {
-                Some(val) => *val += 1,
-                None => {
-                    vents.insert(point, 1);
-                }
-            }
+    }
+}
+
+// The grid point where the infinite lines of two spans with different directions cross, if there is one
+fn crossing(a: &Span, b: &Span) -> Option<(i128, i128)> {
+    use Direction::*;
+    let (a, b) = if a.direction <= b.direction { (a, b) } else { (b, a) };
+    match (a.direction, b.direction) {
+        (Horizontal, Vertical) => Some((b.key, a.key)),
+        (Horizontal, Diagonal) => Some((a.key + b.key, a.key)),
+        (Horizontal, AntiDiagonal) => Some((b.key - a.key, a.key)),
+        (Vertical, Diagonal) => Some((a.key, a.key - b.key)),
+        (Vertical, AntiDiagonal) => Some((a.key, b.key - a.key)),
+        // x - y and x + y always have the same parity on the grid
+        (Diagonal, AntiDiagonal) if (a.key + b.key) % 2 == 0 => Some(((a.key + b.key) / 2, (b.key - a.key) / 2)),
+        _ => None,
+    }
+}
+
+// Start and end of a part of an infinite line
+type Stretch = (i128, i128);
+
+// Sweep over the stretches on one infinite line and return the parts covered
+// at least once and the parts covered at least twice, both sorted and disjoint
+fn coverage(stretches: &[Stretch]) -> (Vec<Stretch>, Vec<Stretch>) {
+    let mut events: Vec<(i128, i32)> = stretches.iter().flat_map(|&(start, end)| [(start, 1), (end + 1, -1)]).collect();
+    events.sort_unstable();
+
+    let (mut covered, mut multiple) = (Vec::new(), Vec::new());
+    let (mut depth, mut covered_start, mut multiple_start) = (0, 0, 0);
+    let mut i = 0;
+    while i < events.len() {
+        let (t, before) = (events[i].0, depth);
+        while i < events.len() && events[i].0 == t {
+            depth += events[i].1;
+            i += 1;
         }
-    } else if line.is_diagonal() {
-        let delta_x: i32 = line.p1.x as i32 - line.p0.x as i32;
-        let delta_y: i32 = line.p1.y as i32 - line.p0.y as i32;
-        let n = delta_x.abs();
-        let delta_x = delta_x.signum();
-        let delta_y = delta_y.signum();
-
-        for i in 0..=n {
-            let point = Point {
-                x: (line.p0.x as i32 + i * delta_x) as u16,
-                y: (line.p0.y as i32 + i * delta_y) as u16,
-            };
-            match vents.get_mut(&point) {
-                Some(val) => *val += 1,
-                None => {
-                    vents.insert(point, 1);
-                }
-            }
+
+        match (before >= 1, depth >= 1) {
+            (false, true) => covered_start = t,
+            (true, false) => covered.push((covered_start, t - 1)),
+            _ => {}
+        }
+        match (before >= 2, depth >= 2) {
+            (false, true) => multiple_start = t,
+            (true, false) => multiple.push((multiple_start, t - 1)),
+            _ => {}
         }
     }
+    (covered, multiple)
 }
 
-fn count_at_least_2(vents: &HashMap<Point, u16>) -> u32 {
-    vents.values().filter(|v| **v >= 2).count() as u32
+fn in_any(stretches: &[Stretch], t: i128) -> bool {
+    let i = stretches.partition_point(|&(_, end)| end < t);
+    stretches.get(i).is_some_and(|&(start, _)| start <= t)
 }
 
-fn part1(lines: &Vec<Line>) -> u32 {
-    let mut vents: HashMap<Point, u16> = HashMap::new();
+/// Same as count_overlaps, but from the overlaps of lines on the same infinite line
+/// and the crossings of different ones, independent of the length of the lines.
+/// Coordinates can span the whole i64 range, so the count is a u128.
+pub fn count_overlaps_analytic(lines: &[Line], with_diagonals: bool) -> u128 {
+    let mut by_line: FxHashMap<(Direction, i128), Vec<Stretch>> = FxHashMap::default();
+    for line in lines.iter().filter(|l| with_diagonals || l.is_axis_parallel()) {
+        let span = Span::new(line);
+        by_line.entry((span.direction, span.key)).or_default().push((span.start, span.end));
+    }
 
-    lines
-        .iter()
-        .filter(|l| l.is_axis_parallel())
-        .for_each(|l| add_vents(&mut vents, l));
+    // points on one line covered at least twice, and the covered parts of all lines
+    let mut multiple = FxHashMap::default();
+    let mut covered = Vec::new();
+    let mut count = 0;
+    for ((direction, key), stretches) in by_line {
+        let (on_line, twice) = coverage(&stretches);
+        covered.extend(on_line.into_iter().map(|(start, end)| Span{direction, key, start, end}));
+        count += twice.iter().map(|(start, end)| (end - start + 1) as u128).sum::<u128>();
+        multiple.insert((direction, key), twice);
+    }
 
-    count_at_least_2(&vents)
-}
+    // sweep from left to right, only parts overlapping in x can cross
+    covered.sort_unstable_by_key(Span::x_range);
+    let mut active: Vec<Span> = Vec::new();
+    let mut crossings = FxHashSet::default();
+    for span in covered {
+        let x_start = span.x_range().0;
+        active.retain(|a| a.x_range().1 >= x_start);
+        for a in &active {
+            if a.direction == span.direction {
+                continue;
+            }
+            if let Some(point) = crossing(a, &span).filter(|p| a.contains(*p) && span.contains(*p)) {
+                crossings.insert(point);
+            }
+        }
+        active.push(span);
+    }
 
-fn part2(lines: &Vec<Line>) -> u32 {
-    let mut vents: HashMap<Point, u16> = HashMap::new();
-    for line in lines {
-        add_vents(&mut vents, line);
+    // a crossing is counted once, whether it is on none, one or several of the overlaps counted so far
+    let directions = [Direction::Horizontal, Direction::Vertical, Direction::Diagonal, Direction::AntiDiagonal];
+    for point in crossings {
+        let n_overlaps = directions
+            .iter()
+            .filter(|&&direction| {
+                let (key, t) = position(direction, point);
+                multiple.get(&(direction, key)).is_some_and(|twice| in_any(twice, t))
+            })
+            .count() as u128;
+        count = count + 1 - n_overlaps;
     }
-    count_at_least_2(&vents)
+    count
 }
 
-pub fn day5() {
+/// The vents drawn on a grid or the overlaps and crossings of the lines
+pub const ALGORITHMS: &[Algorithm<[Line], error::Result<u128>>] = &[
+    Algorithm {
+        name: "raster",
+        part1: |lines| part1(lines).map(|n| n as u128),
+        part2: |lines| part2(lines).map(|n| n as u128),
+    },
+    Algorithm {
+        name: "analytic",
+        part1: |lines| Ok(count_overlaps_analytic(lines, false)),
+        part2: |lines| Ok(count_overlaps_analytic(lines, true)),
+    },
+];
+
+pub fn day5() -> error::Result<()> {
     let input = input::get_input(5);
-    let lines = parse_input(&input);
+    let lines = memory::record("parse", || parse(&input))?;
+
+    let algorithm = algorithms::select(ALGORITHMS)?;
 
     let t0 = Instant::now();
-    println!("Part1: {}", part1(&lines));
-    println!("Part2: {}", part2(&lines));
+    println!("Part1: {}", answer(memory::record("part1", || (algorithm.part1)(&lines))));
+    println!("Part2: {}", answer(memory::record("part2", || (algorithm.part2)(&lines))));
     println!("Time: {} us", t0.elapsed().as_micros());
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    const EXAMPLE: &str = "0,9 -> 5,9
+8,0 -> 0,8
+9,4 -> 3,4
+2,2 -> 2,1
+7,0 -> 7,4
+6,4 -> 2,0
+0,9 -> 2,9
+3,4 -> 1,4
+0,0 -> 8,8
+5,5 -> 8,2
+";
+
+    #[test]
+    fn parts() {
+        let lines = super::parse(EXAMPLE).unwrap();
+        assert_eq!(super::part1(&lines).unwrap(), 5);
+        assert_eq!(super::part2(&lines).unwrap(), 12);
+        assert_eq!(super::count_overlaps_analytic(&lines, false), 5);
+        assert_eq!(super::count_overlaps_analytic(&lines, true), 12);
+    }
+
+    #[test]
+    fn huge_coordinates() {
+        let (min, max) = (i64::MIN, i64::MAX);
+        let input = format!(
+            "{min},0 -> {max},0\n0,0 -> {max},0\n5,{min} -> 5,{max}\n{min},{min} -> {max},{max}\n-3,3 -> 3,-3\n",
+            min = min,
+            max = max
+        );
+        let lines = super::parse(&input).unwrap();
+        // the overlap from 0 to max on the x axis, which contains the crossings at 0 and 5,
+        // and the crossing of the vertical line with the diagonal at 5,5
+        assert_eq!(super::count_overlaps_analytic(&lines, false), max as u128 + 1);
+        assert_eq!(super::count_overlaps_analytic(&lines, true), max as u128 + 2);
+    }
+
+    #[test]
+    fn wide_coordinates() {
+        let raster = &super::ALGORITHMS[0];
+
+        // short lines at the edges of the i64 range can be drawn
+        let input = "9223372036854775800,-9223372036854775808 -> 9223372036854775807,-9223372036854775801
+9223372036854775807,-9223372036854775808 -> 9223372036854775800,-9223372036854775801
+9223372036854775803,-9223372036854775808 -> 9223372036854775803,-9223372036854775800
+";
+        let lines = super::parse(input).unwrap();
+        assert_eq!((raster.part1)(&lines).unwrap(), 0);
+        assert_eq!((raster.part2)(&lines).unwrap(), super::count_overlaps_analytic(&lines, true));
+        assert_eq!((raster.part2)(&lines).unwrap(), 2);
+
+        // a line across the whole range is too long to draw
+        let lines = super::parse("-9223372036854775808,-9223372036854775808 -> 9223372036854775807,9223372036854775807\n").unwrap();
+        assert!((raster.part2)(&lines).is_err());
+        assert_eq!((super::ALGORITHMS[1].part2)(&lines).unwrap(), 0);
+    }
 }
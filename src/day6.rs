@@ -1,56 +1,112 @@
+//! Day 6: Lanternfish
+use crate::animate::Frame;
+use crate::error::{self, answer, ensure, OrInvalid, OrOverflow};
 use crate::input;
+use crate::memory;
 use std::time::Instant;
 
+/// A lanternfish and the days until it creates a new one
 #[derive(Debug, Clone, Copy)]
-struct Fish {
-    timer: u8,
+pub struct Fish {
+    pub timer: u8,
 }
 
-fn parse_input(input: &String) -> Vec<Fish> {
+/// Parse the comma separated timers of the initial fish
+pub fn parse(input: &str) -> error::Result<Vec<Fish>> {
     input
         .trim()
         .split(",")
-        .map(str::parse::<u8>)
-        .map(Result::unwrap)
-        .map(|timer| Fish{timer})
+        .map(|timer| {
+            let timer = timer.trim().parse::<u8>().or_invalid("invalid timer")?;
+            ensure(timer <= 8, "timers must be between 0 and 8")?;
+            Ok(Fish{timer})
+        })
         .collect()
 }
 
-fn simulate_fishes(fishes: &Vec<Fish>, days: i32) -> usize {
-    let mut counts = [0usize; 9];
+/// Number of fish per timer value after one day
+pub struct Generation {
+    pub day: usize,
+    pub counts: [u128; 9],
+    pub total: u128,
+}
 
-    for fish in fishes {
-        counts[fish.timer as usize] += 1;
+impl Generation {
+    // None if the number of fish no longer fits into an u128
+    fn next_day(&self) -> Option<Generation> {
+        let mut counts = [0; 9];
+        counts[..8].copy_from_slice(&self.counts[1..]);
+        counts[8] = self.counts[0];
+        counts[6] = counts[6].checked_add(self.counts[0])?;
+
+        // every fish with timer 0 creates exactly one new fish
+        let total = self.total.checked_add(self.counts[0])?;
+        Some(Generation{day: self.day + 1, counts, total})
     }
+}
 
-    for _ in 0..days {
-        let last_counts = counts.clone();
+/// The generations after each day, starting with the initial state.
+/// Ends before the first day on which the number of fish overflows an u128.
+pub struct Generations {
+    next: Option<Generation>,
+}
+
+impl Iterator for Generations {
+    type Item = Generation;
+
+    fn next(&mut self) -> Option<Generation> {
+        let current = self.next.take()?;
+        self.next = current.next_day();
+        Some(current)
+    }
+}
 
-        for timer in (0..8).rev() {
-            counts[timer] = last_counts[timer + 1];
+impl Frame for Generation {
+    fn render(&self) -> String {
+        let max = *self.counts.iter().max().unwrap_or(&0);
+        let mut s = format!("Day {}, {} lanternfish\n\n", self.day, self.total);
+
+        for (timer, count) in self.counts.iter().enumerate() {
+            let width = if max == 0 { 0 } else { (*count as f64 / max as f64 * 60.0) as usize };
+            s.push_str(&format!("{} \x1b[36m{:<60}\x1b[0m {}\n", timer, "#".repeat(width), count));
         }
 
-        counts[8] = last_counts[0];
-        counts[6] += last_counts[0];
+        s
     }
+}
+
+pub fn snapshots(fishes: &[Fish]) -> Generations {
+    let mut counts = [0u128; 9];
+
+    for fish in fishes {
+        counts[fish.timer as usize] += 1;
+    }
+
+    Generations{next: Some(Generation{day: 0, counts, total: fishes.len() as u128})}
+}
 
-    counts.iter().sum()
+/// Number of fish after the given number of days
+pub fn simulate_fishes(fishes: &[Fish], days: usize) -> error::Result<u128> {
+    snapshots(fishes).nth(days).map(|generation| generation.total).or_overflow()
 }
 
-fn part1(fishes: &Vec<Fish>) -> usize {
-    simulate_fishes(&fishes, 80)
+/// Number of fish after 80 days
+pub fn part1(fishes: &[Fish]) -> error::Result<u128> {
+    simulate_fishes(fishes, 80)
 }
 
-fn part2(fishes: &Vec<Fish>) -> usize {
-    simulate_fishes(&fishes, 256)
+/// Number of fish after 256 days
+pub fn part2(fishes: &[Fish]) -> error::Result<u128> {
+    simulate_fishes(fishes, 256)
 }
 
-pub fn day6() {
+pub fn day6() -> error::Result<()> {
     let input = input::get_input(6);
-    let lines = parse_input(&input);
+    let lines = memory::record("parse", || parse(&input))?;
 
     let t0 = Instant::now();
-    println!("Part1: {}", part1(&lines));
-    println!("Part2: {}", part2(&lines));
+    println!("Part1: {}", answer(memory::record("part1", || part1(&lines))));
+    println!("Part2: {}", answer(memory::record("part2", || part2(&lines))));
     println!("Time: {} us", t0.elapsed().as_micros());
+    Ok(())
 }
@@ -1,63 +1,114 @@
+//! Day 7: The Treachery of Whales
+use crate::algorithms::{self, Algorithm};
+use crate::error::{self, answer, OrInvalid, OrOverflow};
 use crate::input;
+use crate::memory;
+use crate::log::{debug, trace};
 use std::time::Instant;
-use std::cmp::min;
 
 
-fn parse_input(input: &String) -> Vec<i32> {
+/// Parse the comma separated horizontal crab positions
+pub fn parse(input: &str) -> error::Result<Vec<i64>> {
     input
         .trim()
         .split(",")
-        .map(str::parse::<i32>)
-        .map(Result::unwrap)
+        .map(|p| p.trim().parse::<i64>().or_invalid("invalid position"))
         .collect()
 }
 
-fn median(numbers: &Vec<i32>) -> i32 {
-    let mut numbers = numbers.clone();
+fn median(numbers: &[i64]) -> i64 {
+    let mut numbers = numbers.to_vec();
     numbers.sort();
     numbers[numbers.len() / 2]
 }
 
-fn mean(numbers: &Vec<i32>) -> i32 {
-    let sum: f64 = numbers.iter().map(|n| *n as f64).sum();
-    (sum / numbers.len() as f64) as i32
+// Rounded down, the sum of i64 values cannot overflow an i128
+fn mean(numbers: &[i64]) -> i64 {
+    let sum: i128 = numbers.iter().map(|n| *n as i128).sum();
+    sum.div_euclid(numbers.len() as i128) as i64
 }
 
-fn part1(positions: &Vec<i32>) -> i32 {
-    let m = median(&positions);
-
+fn total_distance(positions: &[i64], target_position: i64) -> error::Result<u64> {
     positions
         .iter()
-        .map(|p| (*p - m).abs())
-        .sum()
+        .try_fold(0u64, |total, p| total.checked_add(p.abs_diff(target_position)))
+        .or_overflow()
+}
+
+/// Fuel needed to align all crabs when every step costs one fuel
+pub fn part1(positions: &[i64]) -> error::Result<u64> {
+    let m = median(positions);
+    debug!("part1 alignment position {}", m);
+    total_distance(positions, m)
 }
 
 
-fn fuel_consuption(pos1: i32, pos2: i32) -> i32 {
-    let steps = (pos1 - pos2).abs();
-    (steps * (steps + 1)) / 2
+fn fuel_consuption(pos1: i64, pos2: i64) -> error::Result<u64> {
+    let steps = pos1.abs_diff(pos2) as u128;
+    u64::try_from(steps * (steps + 1) / 2).ok().or_overflow()
 }
 
-fn total_fuel_consumption(positions: &Vec<i32>, target_position: i32) -> i32 {
-    positions.iter()
-        .map(|p| fuel_consuption(*p, target_position))
-        .sum()
+fn total_fuel_consumption(positions: &[i64], target_position: i64) -> error::Result<u64> {
+    positions.iter().try_fold(0u64, |total, p| {
+        total.checked_add(fuel_consuption(*p, target_position)?).or_overflow()
+    })
 }
 
-fn part2(positions: &Vec<i32>) -> i32 {
-    let pos1 = mean(&positions);
+/// Fuel needed to align all crabs when each step costs one more fuel than the last
+pub fn part2(positions: &[i64]) -> error::Result<u64> {
+    // the best position is within 1/2 of the mean
+    let pos1 = mean(positions);
+    let pos2 = pos1.saturating_add(1);
     let fuel1 = total_fuel_consumption(positions, pos1);
-    let fuel2 = total_fuel_consumption(positions, pos1 + 1);
-    println!("{} {}", fuel1, fuel2);
-    min(fuel1, fuel2)
+    let fuel2 = total_fuel_consumption(positions, pos2);
+    trace!("fuel for position {}: {:?}, for position {}: {:?}", pos1, fuel1, pos2, fuel2);
+
+    let (position, fuel) = match (fuel1, fuel2) {
+        (Ok(fuel1), Ok(fuel2)) if fuel2 < fuel1 => (pos2, fuel2),
+        (Ok(fuel1), _) => (pos1, fuel1),
+        (Err(_), Ok(fuel2)) => (pos2, fuel2),
+        (Err(err), Err(_)) => return Err(err),
+    };
+    debug!("part2 alignment position {}", position);
+    Ok(fuel)
+}
+
+// Try every position between the outermost crabs, positions needing too much fuel are skipped
+fn exhaustive(positions: &[i64], total_fuel: fn(&[i64], i64) -> error::Result<u64>) -> error::Result<u64> {
+    let min = *positions.iter().min().unwrap();
+    let max = *positions.iter().max().unwrap();
+
+    (min..=max)
+        .filter_map(|target| total_fuel(positions, target).ok())
+        .min()
+        .or_overflow()
 }
 
-pub fn day7() {
+/// Same as part1, but trying every alignment position
+pub fn part1_exhaustive(positions: &[i64]) -> error::Result<u64> {
+    exhaustive(positions, total_distance)
+}
+
+/// Same as part2, but trying every alignment position
+pub fn part2_exhaustive(positions: &[i64]) -> error::Result<u64> {
+    exhaustive(positions, total_fuel_consumption)
+}
+
+/// The median and mean shortcuts and the exhaustive search
+pub const ALGORITHMS: &[Algorithm<[i64], error::Result<u64>>] = &[
+    Algorithm { name: "median-mean", part1, part2 },
+    Algorithm { name: "exhaustive", part1: part1_exhaustive, part2: part2_exhaustive },
+];
+
+pub fn day7() -> error::Result<()> {
     let input = input::get_input(7);
-    let lines = parse_input(&input);
+    let lines = memory::record("parse", || parse(&input))?;
+
+    let algorithm = algorithms::select(ALGORITHMS)?;
 
     let t0 = Instant::now();
-    println!("Part1: {}", part1(&lines));
-    println!("Part2: {}", part2(&lines));
+    println!("Part1: {}", answer(memory::record("part1", || (algorithm.part1)(&lines))));
+    println!("Part2: {}", answer(memory::record("part2", || (algorithm.part2)(&lines))));
     println!("Time: {} us", t0.elapsed().as_micros());
+    Ok(())
 }
@@ -1,55 +1,56 @@
-use itertools::Itertools;
+//! Day 8: Seven Segment Search
+use crate::error::{self, answer, ensure, OrInvalid};
 use crate::input;
+use crate::memory;
 use std::time::Instant;
-use std::collections::{HashMap,HashSet};
 
 
+/// Segments a to g of a pattern as bits 0 to 6
+pub type Segments = u8;
+
+/// The ten unique signal patterns and the four displayed digits of one display
 #[derive(Debug)]
-struct DisplayConfig {
-    unique_patterns: Vec<String>,
-    displayed_values: Vec<String>
+pub struct DisplayConfig {
+    pub unique_patterns: [Segments; 10],
+    pub displayed_values: [Segments; 4],
 }
 
-fn parse_display(line: &str) -> DisplayConfig {
-    let mut split = line.split(" | ");
-
-    let unique_patterns = split
-        .next()
-        .unwrap()
-        .split_ascii_whitespace()
-        .map(|s| s.chars().sorted().collect::<String>())
-        .collect();
+fn parse_segments(pattern: &str) -> error::Result<Segments> {
+    ensure(pattern.bytes().all(|b| (b'a'..=b'g').contains(&b)), "segments must be a to g")?;
+    Ok(pattern.bytes().fold(0, |mask, b| mask | (1 << (b - b'a'))))
+}
 
-    let displayed_values = split
-        .next()
-        .unwrap()
-        .split_ascii_whitespace()
-        .map(|s| s.chars().sorted().collect::<String>())
-        .collect();
+fn parse_patterns<const N: usize>(patterns: &str) -> error::Result<[Segments; N]> {
+    let mut masks = [0; N];
+    let mut n_patterns = 0;
+    for pattern in patterns.split_ascii_whitespace() {
+        *masks.get_mut(n_patterns).or_invalid("too many patterns")? = parse_segments(pattern)?;
+        n_patterns += 1;
+    }
+    ensure(n_patterns == N, "too few patterns")?;
+    Ok(masks)
+}
 
-    DisplayConfig{unique_patterns, displayed_values}
+pub fn parse_display(line: &str) -> error::Result<DisplayConfig> {
+    let (unique_patterns, displayed_values) = line.split_once(" | ").or_invalid("expected patterns | values")?;
 
+    Ok(DisplayConfig{
+        unique_patterns: parse_patterns(unique_patterns)?,
+        displayed_values: parse_patterns(displayed_values)?,
+    })
 }
 
-fn parse_input(input: &String) -> Vec<DisplayConfig> {
-    input
-        .lines()
-        .map(parse_display)
-        .collect()
+/// Parse the displays, one per line
+pub fn parse(input: &str) -> error::Result<Vec<DisplayConfig>> {
+    input::parse_lines(input, parse_display)
 }
 
-fn part1(positions: &Vec<DisplayConfig>) -> i32 {
-    let unique_lens: HashMap<u8, u8> = [
-        (2, 1),
-        (4, 4),
-        (3, 7),
-        (7, 8),
-    ].iter().cloned().collect();
-
+/// Number of displayed 1, 4, 7 and 8 digits
+pub fn part1(positions: &[DisplayConfig]) -> usize {
     let mut n_unique = 0;
     for position in positions {
         for value in &position.displayed_values {
-            if unique_lens.contains_key(&(value.len() as u8)) {
+            if matches!(value.count_ones(), 2 | 3 | 4 | 7) {
                 n_unique += 1
             }
         }
@@ -58,25 +59,24 @@ fn part1(positions: &Vec<DisplayConfig>) -> i32 {
     n_unique
 }
 
-fn find_value_with_len(patterns: &Vec<String>, len: usize) -> HashSet<char>{
+fn find_value_with_len(patterns: &[Segments], len: u32) -> error::Result<Segments> {
     patterns
         .iter()
-        .find(|s| s.len() == len)
-        .unwrap()
-        .chars()
-        .collect()
+        .find(|s| s.count_ones() == len)
+        .copied()
+        .ok_or_else(|| error::Error::Parse(format!("no pattern with {} segments", len)))
 }
 
-fn determine_value(display: &DisplayConfig) -> i32 {
-    let one = find_value_with_len(&display.unique_patterns, 2);
-    let four = find_value_with_len(&display.unique_patterns, 4);
+/// Decode the four digit number shown on the display
+pub fn determine_value(display: &DisplayConfig) -> error::Result<u32> {
+    let one = find_value_with_len(&display.unique_patterns, 2)?;
+    let four = find_value_with_len(&display.unique_patterns, 4)?;
 
     let mut value = 0;
     for (i, number) in display.displayed_values.iter().enumerate() {
-        let set: HashSet<char> = number.chars().collect();
-        let length = number.len();
-        let intersect_one = one.intersection(&set).count();
-        let intersect_four = four.intersection(&set).count();
+        let length = number.count_ones();
+        let intersect_one = (one & number).count_ones();
+        let intersect_four = (four & number).count_ones();
 
         let digit = match (length, intersect_one, intersect_four) {
             (2, _, _) => 1,
@@ -92,25 +92,49 @@ fn determine_value(display: &DisplayConfig) -> i32 {
             (6, 2, 3) => 0,
             (6, 2, 4) => 9,
 
-            _ => panic!("Unexpected pattern")
+            _ => return Err(error::Error::Parse(format!("displayed pattern {:07b} is no digit", number))),
         };
 
-        value += 10i32.pow((3 - i) as u32) * digit;
+        value += 10u32.pow((3 - i) as u32) * digit;
     }
 
-    value
+    Ok(value)
 }
 
-fn part2(displays: &Vec<DisplayConfig>) -> i32 {
-    displays.iter().map(determine_value).sum()
+/// Sum of all decoded displayed numbers, in u64 so that it cannot overflow
+pub fn part2(displays: &[DisplayConfig]) -> error::Result<u64> {
+    displays.iter().map(|display| determine_value(display).map(u64::from)).sum()
 }
 
-pub fn day8() {
+pub fn day8() -> error::Result<()> {
     let input = input::get_input(8);
-    let displays = parse_input(&input);
+    let displays = memory::record("parse", || parse(&input))?;
 
     let t0 = Instant::now();
-    println!("Part1: {}", part1(&displays));
-    println!("Part2: {}", part2(&displays));
+    println!("Part1: {}", memory::record("part1", || part1(&displays)));
+    println!("Part2: {}", answer(memory::record("part2", || part2(&displays))));
     println!("Time: {} us", t0.elapsed().as_micros());
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    const DISPLAY: &str = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+
+    #[test]
+    fn part2() {
+        let displays = super::parse(DISPLAY).unwrap();
+        assert_eq!(super::part2(&displays), Ok(5353));
+    }
+
+    #[test]
+    fn invalid_displays() {
+        // no pattern of length two, so the one is missing
+        let displays = super::parse("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb abc | cdfeb fcadb cdfeb cdbaf").unwrap();
+        assert!(super::part2(&displays).is_err());
+
+        // five segments sharing nothing with the one
+        let displays = super::parse("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdefg fcadb cdfeb cdbaf").unwrap();
+        assert!(super::part2(&displays).is_err());
+    }
 }
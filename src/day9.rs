@@ -1,14 +1,15 @@
+//! Day 9: Smoke Basin
+use crate::error::{self, answer, OrOverflow};
 use crate::input;
+use crate::memory;
 use std::time::Instant;
 use std::collections::VecDeque;
 use itertools::Itertools;
 
 
-fn parse_input(input: &String) -> Vec<Vec<u8>> {
-    input
-        .lines()
-        .map(|l| l.chars().map(|c| c as u8 - b'0').collect())
-        .collect()
+/// Parse the height map, one row of digits per line
+pub fn parse(input: &str) -> error::Result<Vec<Vec<u8>>> {
+    input::parse_digit_grid(input)
 }
 
 
@@ -20,7 +21,7 @@ const DIRECTIONS: [(i32, i32); 4] = [
 ];
 
 
-fn is_min(grid: &Vec<Vec<u8>>, row: i32, col: i32) -> bool {
+fn is_min(grid: &[Vec<u8>], row: i32, col: i32) -> bool {
     let value = grid[row as usize][col as usize];
     let n_rows: i32 = grid.len().try_into().unwrap();
     let n_cols: i32 = grid[0].len().try_into().unwrap();
@@ -32,7 +33,8 @@ fn is_min(grid: &Vec<Vec<u8>>, row: i32, col: i32) -> bool {
         .all(|(r, c)| grid[r as usize][c as usize] > value)
 }
 
-fn local_minima(grid: &Vec<Vec<u8>>) -> Vec<(usize, usize)> {
+/// Row and column of all low points
+pub fn local_minima(grid: &[Vec<u8>]) -> Vec<(usize, usize)> {
     let mut minima = Vec::new();
     let n_rows = grid.len();
     let n_cols = grid[0].len();
@@ -49,7 +51,7 @@ fn local_minima(grid: &Vec<Vec<u8>>) -> Vec<(usize, usize)> {
 }
 
 
-fn basin_size(row: usize, col: usize, grid: &Vec<Vec<u8>>, visited: &mut Vec<Vec<bool>>) -> usize {
+fn basin_size(row: usize, col: usize, grid: &[Vec<u8>], visited: &mut [Vec<bool>]) -> usize {
     let mut size = 1;
     let mut to_check: VecDeque<(usize, usize)> = VecDeque::new();
 
@@ -69,7 +71,7 @@ fn basin_size(row: usize, col: usize, grid: &Vec<Vec<u8>>, visited: &mut Vec<Vec
         .for_each(|(r, c)| to_check.push_back((r as usize, c as usize)));
 
 
-    while to_check.len() > 0 {
+    while !to_check.is_empty() {
         let (r, c) = to_check.pop_front().unwrap();
 
         if grid[r][c] < 9 && !visited[r][c] {
@@ -89,11 +91,47 @@ fn basin_size(row: usize, col: usize, grid: &Vec<Vec<u8>>, visited: &mut Vec<Vec
     size
 }
 
-fn part1(grid: &Vec<Vec<u8>>) -> usize {
+/// The basin of every position, numbered in the order of the low points, None for the ridges of height 9
+pub fn basins(grid: &[Vec<u8>]) -> Vec<Vec<Option<usize>>> {
+    let n_rows = grid.len();
+    let n_cols = grid[0].len();
+    let mut labels = vec![vec![None; n_cols]; n_rows];
+    let mut n_basins = 0;
+
+    for (row, col) in local_minima(grid) {
+        if labels[row][col].is_some() || grid[row][col] == 9 {
+            continue;
+        }
+
+        let mut to_check = VecDeque::from([(row, col)]);
+        labels[row][col] = Some(n_basins);
+
+        while let Some((r, c)) = to_check.pop_front() {
+            for (drow, dcol) in DIRECTIONS {
+                let (nr, nc) = (r as i32 + drow, c as i32 + dcol);
+                if nr < 0 || nr >= n_rows as i32 || nc < 0 || nc >= n_cols as i32 {
+                    continue;
+                }
+                let (nr, nc) = (nr as usize, nc as usize);
+                if grid[nr][nc] < 9 && labels[nr][nc].is_none() {
+                    labels[nr][nc] = Some(n_basins);
+                    to_check.push_back((nr, nc));
+                }
+            }
+        }
+        n_basins += 1;
+    }
+
+    labels
+}
+
+/// Sum of the risk levels of all low points
+pub fn part1(grid: &[Vec<u8>]) -> usize {
     local_minima(grid).iter().map(|(r, c)| grid[*r][*c] as usize + 1).sum()
 }
 
-fn part2(grid: &Vec<Vec<u8>>) -> usize {
+/// Product of the sizes of the three largest basins
+pub fn part2(grid: &[Vec<u8>]) -> error::Result<usize> {
     let n_rows = grid.len();
     let n_cols = grid[0].len();
     let mut visited: Vec<Vec<bool>> = vec![vec![false; n_cols]; n_rows];
@@ -103,15 +141,17 @@ fn part2(grid: &Vec<Vec<u8>>) -> usize {
         .sorted()
         .rev()
         .take(3)
-        .product()
+        .try_fold(1usize, |product, size| product.checked_mul(size))
+        .or_overflow()
 }
 
-pub fn day9() {
+pub fn day9() -> error::Result<()> {
     let input = input::get_input(9);
-    let grid = parse_input(&input);
+    let grid = memory::record("parse", || parse(&input))?;
 
     let t0 = Instant::now();
-    println!("Part1: {}", part1(&grid));
-    println!("Part2: {}", part2(&grid));
+    println!("Part1: {}", memory::record("part1", || part1(&grid)));
+    println!("Part2: {}", answer(memory::record("part2", || part2(&grid))));
     println!("Time: {} us", t0.elapsed().as_micros());
+    Ok(())
 }
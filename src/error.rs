@@ -0,0 +1,89 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// An intermediate value or the answer does not fit into its integer type
+    Overflow,
+    /// The puzzle input is malformed
+    Parse(String),
+    /// Reading or writing a file failed
+    Io(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Overflow => write!(f, "Arithmetic overflow"),
+            Error::Parse(message) => write!(f, "Invalid input, {}", message),
+            Error::Io(message) => write!(f, "I/O error, {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+// Format an answer for printing, or the reason why there is none
+pub fn answer<T: fmt::Display>(result: Result<T>) -> String {
+    match result {
+        Ok(value) => value.to_string(),
+        Err(err) => format!("Error: {}", err),
+    }
+}
+
+// Turn the None of the checked_* integer operations into an overflow error
+pub(crate) trait OrOverflow<T> {
+    fn or_overflow(self) -> Result<T>;
+}
+
+impl<T> OrOverflow<T> for Option<T> {
+    fn or_overflow(self) -> Result<T> {
+        self.ok_or(Error::Overflow)
+    }
+}
+
+// Turn a missing or unparsable part of the input into a parse error
+pub(crate) trait OrInvalid<T> {
+    fn or_invalid(self, what: &str) -> Result<T>;
+}
+
+impl<T> OrInvalid<T> for Option<T> {
+    fn or_invalid(self, what: &str) -> Result<T> {
+        self.ok_or_else(|| Error::Parse(what.to_string()))
+    }
+}
+
+impl<T, E: fmt::Display> OrInvalid<T> for std::result::Result<T, E> {
+    fn or_invalid(self, what: &str) -> Result<T> {
+        self.map_err(|err| Error::Parse(format!("{}: {}", what, err)))
+    }
+}
+
+// Turn a failed file operation into an I/O error
+pub(crate) trait OrIo<T> {
+    fn or_io(self, what: &str) -> Result<T>;
+}
+
+impl<T> OrIo<T> for std::io::Result<T> {
+    fn or_io(self, what: &str) -> Result<T> {
+        self.map_err(|err| Error::Io(format!("{}: {}", what, err)))
+    }
+}
+
+// Parse error unless the condition holds
+pub(crate) fn ensure(condition: bool, what: &str) -> Result<()> {
+    if condition {
+        Ok(())
+    } else {
+        Err(Error::Parse(what.to_string()))
+    }
+}
+
+// Prefix parse errors with the (1-based) line they occurred in
+pub(crate) fn in_line<T>(result: Result<T>, line: usize) -> Result<T> {
+    result.map_err(|err| match err {
+        Error::Parse(message) => Error::Parse(format!("line {}: {}", line, message)),
+        err => err,
+    })
+}
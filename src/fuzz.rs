@@ -0,0 +1,53 @@
+//! Entry point for fuzzing the input parsers.
+//!
+//! The cargo-fuzz targets in `fuzz/` and the in-tree corpus runner feed
+//! arbitrary bytes through [`parse`]. Malformed input has to be reported as
+//! an error, every panic is a bug.
+use crate::*;
+use std::ops::RangeInclusive;
+
+/// The days with a parser
+pub const DAYS: RangeInclusive<u8> = 1..=18;
+
+/// Run the parsers of the given day, including the streaming ones, on arbitrary bytes
+pub fn parse(day: u8, data: &[u8]) {
+    let input = String::from_utf8_lossy(data);
+
+    match day {
+        1 => {
+            let _ = day1::parse(&input);
+            let _ = day1::solve_reader(data);
+        }
+        2 => {
+            let _ = day2::parse(&input);
+            let _ = day2::solve_reader(data);
+        }
+        3 => {
+            let _ = day3::parse(&input);
+            let _ = day3::part1_reader(data);
+        }
+        4 => drop(day4::parse(&input)),
+        5 => drop(day5::parse(&input)),
+        6 => drop(day6::parse(&input)),
+        7 => drop(day7::parse(&input)),
+        8 => {
+            if let Ok(displays) = day8::parse(&input) {
+                let _ = day8::part2(&displays);
+            }
+        }
+        9 => drop(day9::parse(&input)),
+        10 => {
+            let _ = day10::parse(&input);
+            let _ = day10::solve_reader(data);
+        }
+        11 => drop(day11::parse(&input)),
+        12 => drop(day12::parse(&input)),
+        13 => drop(day13::parse(&input)),
+        14 => drop(day14::parse(&input)),
+        15 => drop(day15::parse(&input)),
+        16 => drop(day16::parse(&input)),
+        17 => drop(day17::parse(&input)),
+        18 => drop(day18::parse(&input)),
+        _ => {}
+    }
+}
@@ -0,0 +1,119 @@
+//! A fast, non-cryptographic hasher in the style of the FxHash used by rustc.
+//!
+//! Its hashes are easy to predict, so it must not be used for keys controlled
+//! by an attacker, but for the small integer keys of the puzzles it is much
+//! cheaper than the default SipHash.
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasherDefault, Hasher};
+
+const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+/// Mixes every written word into the hash with a rotate, xor and multiply
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FxHasher {
+    hash: u64,
+}
+
+impl FxHasher {
+    #[inline]
+    fn add_to_hash(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(SEED);
+    }
+}
+
+impl Hasher for FxHasher {
+    // Words of eight bytes, then the rest in at most one piece of four, two and one byte each
+    #[inline]
+    fn write(&mut self, mut bytes: &[u8]) {
+        while bytes.len() >= 8 {
+            self.add_to_hash(u64::from_le_bytes(bytes[..8].try_into().unwrap()));
+            bytes = &bytes[8..];
+        }
+        if bytes.len() >= 4 {
+            self.add_to_hash(u32::from_le_bytes(bytes[..4].try_into().unwrap()) as u64);
+            bytes = &bytes[4..];
+        }
+        if bytes.len() >= 2 {
+            self.add_to_hash(u16::from_le_bytes(bytes[..2].try_into().unwrap()) as u64);
+            bytes = &bytes[2..];
+        }
+        if let Some(byte) = bytes.first() {
+            self.add_to_hash(*byte as u64);
+        }
+    }
+
+    #[inline]
+    fn write_u8(&mut self, i: u8) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn write_u16(&mut self, i: u16) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn write_u32(&mut self, i: u32) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        self.add_to_hash(i);
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+pub type FxBuildHasher = BuildHasherDefault<FxHasher>;
+pub type FxHashMap<K, V> = HashMap<K, V, FxBuildHasher>;
+pub type FxHashSet<T> = HashSet<T, FxBuildHasher>;
+
+#[cfg(test)]
+mod test {
+    use super::FxHasher;
+    use std::hash::{Hash, Hasher};
+
+    fn hash<T: Hash>(value: T) -> u64 {
+        let mut hasher = FxHasher::default();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn distinct_small_keys() {
+        let mut hashes: Vec<u64> = (0..100u32).flat_map(|x| (0..100u32).map(move |y| hash((x, y)))).collect();
+        hashes.sort_unstable();
+        hashes.dedup();
+        assert_eq!(hashes.len(), 100 * 100);
+    }
+
+    fn hash_bytes(bytes: &[u8]) -> u64 {
+        let mut hasher = FxHasher::default();
+        hasher.write(bytes);
+        hasher.finish()
+    }
+
+    // The hashes of the 64 bit FxHasher of the rustc-hash crate, version 1.1
+    #[test]
+    fn reference_hashes() {
+        assert_eq!(hash(7u64), 0x3a69_4c02_11ee_4a13);
+        assert_eq!(hash(7u8), 0x3a69_4c02_11ee_4a13);
+        assert_eq!(hash((1u64, 2u64)), 0x6a4b_e67f_f98f_abc8);
+        assert_eq!(hash((3u32, 4u16)), 0xe1dd_36ee_3af3_1882);
+
+        assert_eq!(hash_bytes(&[1, 2, 3, 4, 5, 6, 7, 8]), 0xeebe_e07e_def6_3495);
+        assert_eq!(hash_bytes(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]), 0x6e8b_5d1d_34f0_8a49);
+        assert_eq!(hash_bytes(&[1, 2]), 0x4b00_3005_6b37_3495);
+        assert_eq!(hash("ab"), 0x3326_e72f_b14c_bfa7);
+        assert_eq!(hash("ba"), 0xb231_ccde_36c9_2605);
+    }
+}
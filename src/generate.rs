@@ -0,0 +1,471 @@
+// Generators for random, but valid puzzle inputs of arbitrary size,
+// used to profile the solutions on inputs larger than the real ones.
+use crate::rng::Rng;
+use itertools::Itertools;
+use std::collections::HashSet;
+
+// The size parameter is day specific, these are roughly the sizes of the real inputs
+const DEFAULT_SIZES: [usize; 18] = [2000, 1000, 1000, 100, 500, 300, 1000, 200, 100, 100, 10, 12, 800, 20, 100, 50, 100, 100];
+
+fn day1(rng: &mut Rng, size: usize) -> String {
+    let mut depth = rng.range(100..=200);
+    (0..size)
+        .map(|_| {
+            depth = (depth + rng.range(-10..=30)).max(0);
+            depth
+        })
+        .join("\n")
+}
+
+fn day2(rng: &mut Rng, size: usize) -> String {
+    let mut aim = 0;
+    (0..size)
+        .map(|_| {
+            let amount = rng.range(1..=9);
+            match rng.below(3) {
+                0 => format!("forward {}", amount),
+                1 if aim >= amount => {
+                    aim -= amount;
+                    format!("up {}", amount)
+                }
+                _ => {
+                    aim += amount;
+                    format!("down {}", amount)
+                }
+            }
+        })
+        .join("\n")
+}
+
+fn day3(rng: &mut Rng, size: usize) -> String {
+    let n_bits = (usize::BITS - size.leading_zeros()).clamp(12, 16) as usize;
+    let size = size.min(1 << n_bits);
+
+    // Distinct numbers can still leave the CO2 rating with only numbers sharing
+    // a bit, so draw until both ratings exist
+    let mut numbers = loop {
+        let mut seen = HashSet::new();
+        while seen.len() < size {
+            seen.insert(rng.below(1 << n_bits));
+        }
+
+        let numbers = seen.into_iter().sorted_unstable().collect_vec();
+        if crate::day3::ratings(n_bits, &numbers, crate::day3::OXYGEN, crate::day3::CO2).is_ok() {
+            break numbers;
+        }
+    };
+    rng.shuffle(&mut numbers);
+    numbers.iter().map(|n| format!("{:0width$b}", n, width = n_bits)).join("\n")
+}
+
+fn day4(rng: &mut Rng, size: usize) -> String {
+    // more boards draw from more numbers, so that the games get longer
+    let mut numbers: Vec<u32> = (0..size.max(100) as u32).collect();
+    rng.shuffle(&mut numbers);
+    let mut output = numbers.iter().join(",");
+
+    for _ in 0..size {
+        // only the first 25 numbers need to be shuffled for a board
+        for i in 0..25 {
+            let j = i + rng.below((numbers.len() - i) as u64) as usize;
+            numbers.swap(i, j);
+        }
+        output.push_str("\n\n");
+        output.push_str(&numbers[..25].chunks(5).map(|row| row.iter().map(|n| format!("{:2}", n)).join(" ")).join("\n"));
+    }
+
+    output
+}
+
+fn day5(rng: &mut Rng, size: usize) -> String {
+    let extent = (2 * size as i64).clamp(1000, u16::MAX as i64);
+
+    (0..size)
+        .map(|_| {
+            let x0 = rng.range(0..=extent - 1);
+            let y0 = rng.range(0..=extent - 1);
+            let (x1, y1) = match rng.below(3) {
+                0 => (rng.range(0..=extent - 1), y0),
+                1 => (x0, rng.range(0..=extent - 1)),
+                _ => {
+                    let dx = *rng.choose(&[-1, 1]);
+                    let dy = *rng.choose(&[-1, 1]);
+                    // stay inside the grid in both directions
+                    let max_x = if dx > 0 { extent - 1 - x0 } else { x0 };
+                    let max_y = if dy > 0 { extent - 1 - y0 } else { y0 };
+                    let n = rng.range(0..=max_x.min(max_y));
+                    (x0 + dx * n, y0 + dy * n)
+                }
+            };
+            format!("{},{} -> {},{}", x0, y0, x1, y1)
+        })
+        .join("\n")
+}
+
+fn day6(rng: &mut Rng, size: usize) -> String {
+    (0..size).map(|_| rng.range(1..=5)).join(",")
+}
+
+fn day7(rng: &mut Rng, size: usize) -> String {
+    (0..size).map(|_| rng.range(0..=2 * size as i64)).join(",")
+}
+
+const SEGMENTS: [&str; 10] = ["abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg"];
+
+fn scramble(rng: &mut Rng, wiring: &[char], digit: usize) -> String {
+    let mut wires = SEGMENTS[digit].bytes().map(|s| wiring[(s - b'a') as usize]).collect_vec();
+    rng.shuffle(&mut wires);
+    wires.into_iter().collect()
+}
+
+fn day8(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let mut wiring: Vec<char> = "abcdefg".chars().collect();
+            rng.shuffle(&mut wiring);
+
+            let mut patterns = (0..10).map(|digit| scramble(rng, &wiring, digit)).collect_vec();
+            rng.shuffle(&mut patterns);
+
+            let displayed = (0..4)
+                .map(|_| {
+                    let digit = rng.below(10) as usize;
+                    scramble(rng, &wiring, digit)
+                })
+                .collect_vec();
+
+            format!("{} | {}", patterns.join(" "), displayed.join(" "))
+        })
+        .join("\n")
+}
+
+fn digit_grid(rng: &mut Rng, size: usize, digit: impl Fn(&mut Rng) -> i64) -> String {
+    (0..size).map(|_| (0..size).map(|_| digit(rng)).join("")).join("\n")
+}
+
+fn day9(rng: &mut Rng, size: usize) -> String {
+    digit_grid(rng, size, |rng| if rng.below(10) < 3 { 9 } else { rng.range(0..=8) })
+}
+
+const OPENING: [char; 4] = ['(', '[', '{', '<'];
+const CLOSING: [char; 4] = [')', ']', '}', '>'];
+
+fn day10(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|i| {
+            let mut line = String::new();
+            let mut stack = Vec::new();
+
+            // keep the stack shallow, completion scores grow with 5^depth
+            for _ in 0..rng.range(20..=110) {
+                if stack.is_empty() || (stack.len() < 15 && rng.below(2) == 0) {
+                    let bracket = rng.below(4) as usize;
+                    stack.push(bracket);
+                    line.push(OPENING[bracket]);
+                } else {
+                    line.push(CLOSING[stack.pop().unwrap()]);
+                }
+            }
+
+            if stack.is_empty() {
+                let bracket = rng.below(4) as usize;
+                stack.push(bracket);
+                line.push(OPENING[bracket]);
+            }
+
+            // every other line is corrupted, so there always is an incomplete one
+            if i % 2 == 1 {
+                let expected = *stack.last().unwrap() as u64;
+                let wrong = (expected + 1 + rng.below(3)) % 4;
+                line.push(CLOSING[wrong as usize]);
+            }
+
+            line
+        })
+        .join("\n")
+}
+
+fn day11(rng: &mut Rng, _size: usize) -> String {
+    // the octopus grid has a fixed size, and not every grid ever synchronizes
+    loop {
+        let input = digit_grid(rng, 10, |rng| rng.range(0..=9));
+        let mut grid = crate::day11::parse(&input).expect("generated grid is valid");
+
+        if (0..1000).any(|_| crate::day11::do_step(&mut grid) == 100) {
+            return input;
+        }
+    }
+}
+
+fn cave_name(rng: &mut Rng, names: &mut HashSet<String>, big: bool) -> String {
+    loop {
+        let name: String = (0..2).map(|_| (b'a' + rng.below(26) as u8) as char).collect();
+        let name = if big { name.to_ascii_uppercase() } else { name };
+
+        if names.insert(name.clone()) {
+            return name;
+        }
+    }
+}
+
+// Caves of one part of the cave system, connected to start, end and nothing else
+fn cave_cluster(rng: &mut Rng, names: &mut HashSet<String>, size: usize, edges: &mut HashSet<(String, String)>) {
+    let n_big = (size / 5).max(1);
+    let n_small = size.saturating_sub(n_big).max(1);
+
+    let small = (0..n_small).map(|_| cave_name(rng, names, false)).collect_vec();
+    let big = (0..n_big).map(|_| cave_name(rng, names, true)).collect_vec();
+
+    // a spanning tree over start and the small caves keeps everything connected
+    for (i, cave) in small.iter().enumerate() {
+        let other = if i == 0 || rng.below(4) == 0 { "start" } else { &small[rng.below(i as u64) as usize] };
+        edges.insert((other.to_string(), cave.clone()));
+    }
+
+    for _ in 0..n_small / 4 {
+        let a = rng.choose(&small).clone();
+        let b = rng.choose(&small).clone();
+        if a != b && !edges.contains(&(b.clone(), a.clone())) {
+            edges.insert((a, b));
+        }
+    }
+
+    // two big caves must never be connected, otherwise there are infinitely many paths
+    for cave in &big {
+        for _ in 0..rng.range(2..=3) {
+            edges.insert((cave.clone(), rng.choose(&small).clone()));
+        }
+    }
+
+    edges.insert((rng.choose(&small).clone(), "end".to_string()));
+    edges.insert((rng.choose(&big).clone(), "end".to_string()));
+}
+
+// The number of paths grows exponentially with the caves they can pass, so larger
+// inputs consist of clusters about the size of the real input, which only meet at
+// start and end. The number of paths then only grows with the number of clusters.
+const CAVE_CLUSTER_SIZE: usize = 12;
+
+fn day12(rng: &mut Rng, size: usize) -> String {
+    // there are only so many two letter names
+    let mut size = size.clamp(1, 500);
+    let mut names = HashSet::new();
+    let mut edges = HashSet::new();
+
+    while size > 0 {
+        let cluster = size.min(CAVE_CLUSTER_SIZE);
+        cave_cluster(rng, &mut names, cluster, &mut edges);
+        size -= cluster;
+    }
+
+    let mut edges = edges.into_iter().map(|(a, b)| format!("{}-{}", a, b)).collect_vec();
+    edges.sort_unstable();
+    rng.shuffle(&mut edges);
+    edges.join("\n")
+}
+
+fn day13(rng: &mut Rng, size: usize) -> String {
+    let n_folds = 6;
+    let mut width = 40;
+    let mut height = 6;
+    let mut folds = Vec::new();
+
+    // unfold the final grid, the first fold is at the center of the paper
+    for _ in 0..n_folds {
+        folds.push(('y', height));
+        height = 2 * height + 1;
+        folds.push(('x', width));
+        width = 2 * width + 1;
+    }
+    folds.reverse();
+
+    let mut dots = HashSet::new();
+    for _ in 0..size {
+        let mut x = rng.range(0..=39);
+        let mut y = rng.range(0..=5);
+
+        for (axis, position) in folds.iter().rev() {
+            if rng.below(2) == 0 {
+                continue;
+            }
+            match axis {
+                'x' => x = 2 * position - x,
+                _ => y = 2 * position - y,
+            }
+        }
+        dots.insert((x, y));
+    }
+
+    let mut dots = dots.into_iter().collect_vec();
+    dots.sort_unstable();
+    rng.shuffle(&mut dots);
+
+    format!(
+        "{}\n\n{}",
+        dots.iter().map(|(x, y)| format!("{},{}", x, y)).join("\n"),
+        folds.iter().map(|(axis, position)| format!("fold along {}={}", axis, position)).join("\n"),
+    )
+}
+
+fn day14(rng: &mut Rng, size: usize) -> String {
+    let elements = ['B', 'C', 'F', 'H', 'K', 'N', 'O', 'P', 'S', 'V'];
+    let template: String = (0..size.max(2)).map(|_| *rng.choose(&elements)).collect();
+    let rules = elements
+        .iter()
+        .cartesian_product(elements.iter())
+        .map(|(a, b)| format!("{}{} -> {}", a, b, rng.choose(&elements)))
+        .join("\n");
+
+    format!("{}\n\n{}", template, rules)
+}
+
+fn day15(rng: &mut Rng, size: usize) -> String {
+    digit_grid(rng, size, |rng| rng.range(1..=9))
+}
+
+fn push_bits(bits: &mut Vec<u8>, value: u64, n_bits: u8) {
+    for bit in (0..n_bits).rev() {
+        bits.push(((value >> bit) & 1) as u8);
+    }
+}
+
+// Every operator uses up one packet of the budget and passes the rest on to
+// its first child, which results in deeply nested packets, but never deeper
+// than the parser accepts. Returns the value of the packet, sums and products
+// that would not fit into an u64 become minimums, so the result never overflows.
+fn bits_packet(rng: &mut Rng, budget: &mut usize, depth: usize, bits: &mut Vec<u8>) -> u64 {
+    push_bits(bits, rng.below(8), 3);
+
+    if *budget <= 1 || depth + 1 >= crate::day16::MAX_DEPTH || rng.below(5) == 0 {
+        *budget = budget.saturating_sub(1);
+        push_bits(bits, 4, 3);
+
+        let value = if rng.below(4) == 0 { rng.below(1 << 16) } else { rng.below(16) };
+        let n_groups = (1..).find(|n| value < 1 << (4 * n)).unwrap();
+        for group in (0..n_groups).rev() {
+            push_bits(bits, (group > 0) as u64, 1);
+            push_bits(bits, (value >> (4 * group)) & 0xf, 4);
+        }
+        return value;
+    }
+
+    *budget -= 1;
+    let type_id = *rng.choose(&[0, 0, 1, 2, 2, 3, 3, 5, 6, 7]);
+    let n_packets = match type_id {
+        5..=7 => 2,
+        1 => 2,
+        _ => rng.range(1..=4),
+    };
+
+    let mut children = Vec::new();
+    let values = (0..n_packets).map(|_| bits_packet(rng, budget, depth + 1, &mut children)).collect_vec();
+
+    let value = match type_id {
+        0 => values.iter().try_fold(0u64, |sum, v| sum.checked_add(*v)),
+        1 => values.iter().try_fold(1u64, |product, v| product.checked_mul(*v)),
+        3 => values.iter().max().copied(),
+        5 => Some((values[0] > values[1]) as u64),
+        6 => Some((values[0] < values[1]) as u64),
+        7 => Some((values[0] == values[1]) as u64),
+        _ => None,
+    };
+    let (type_id, value) = match value {
+        Some(value) => (type_id, value),
+        None => (2, *values.iter().min().unwrap()),
+    };
+    push_bits(bits, type_id, 3);
+
+    if children.len() < (1 << 15) && rng.below(2) == 0 {
+        push_bits(bits, 0, 1);
+        push_bits(bits, children.len() as u64, 15);
+    } else {
+        push_bits(bits, 1, 1);
+        push_bits(bits, n_packets as u64, 11);
+    }
+    bits.extend(children);
+    value
+}
+
+fn day16(rng: &mut Rng, size: usize) -> String {
+    let mut bits = Vec::new();
+    let mut budget = size;
+    bits_packet(rng, &mut budget, 0, &mut bits);
+
+    while bits.len() % 4 != 0 {
+        bits.push(0);
+    }
+
+    bits.chunks(4)
+        .map(|nibble| nibble.iter().fold(0, |acc, bit| (acc << 1) | *bit as u32))
+        .map(|nibble| std::char::from_digit(nibble, 16).unwrap().to_ascii_uppercase())
+        .collect()
+}
+
+fn day17(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(4) as i64;
+    let min_x = rng.range(size..=2 * size);
+    let max_x = min_x + rng.range(size / 4..=size / 2);
+    let max_y = -rng.range(size / 2..=size);
+    let min_y = max_y - rng.range(size / 4..=size / 2);
+    format!("target area: x={}..{}, y={}..{}", min_x, max_x, min_y, max_y)
+}
+
+// Only pairs nested inside four pairs explode, so this number is already reduced
+fn snailfish_number(rng: &mut Rng, depth: usize) -> String {
+    if depth == 4 || (depth > 0 && rng.below(3) == 0) {
+        return rng.range(0..=9).to_string();
+    }
+    format!("[{},{}]", snailfish_number(rng, depth + 1), snailfish_number(rng, depth + 1))
+}
+
+fn day18(rng: &mut Rng, size: usize) -> String {
+    (0..size).map(|_| snailfish_number(rng, 0)).join("\n")
+}
+
+pub fn generate(day: u8, size: Option<usize>, seed: u64) -> Option<String> {
+    let generators: [fn(&mut Rng, usize) -> String; 18] = [
+        day1, day2, day3, day4, day5, day6, day7, day8, day9, day10, day11, day12, day13, day14, day15, day16, day17,
+        day18,
+    ];
+
+    let index = (day as usize).checked_sub(1).filter(|i| *i < generators.len())?;
+    let size = size.unwrap_or(DEFAULT_SIZES[index]);
+    let mut rng = Rng::new(seed);
+
+    Some(generators[index](&mut rng, size) + "\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::generate;
+    use crate::{day12, day16, day3};
+
+    #[test]
+    fn cave_paths() {
+        for size in [1, 12, 100, 500] {
+            let input = generate(12, Some(size), 1).unwrap();
+            let caves = day12::parse(&input).unwrap();
+            assert!(day12::part2(&caves) >= day12::part1(&caves));
+        }
+    }
+
+    #[test]
+    fn ratings() {
+        for size in [1, 10, 100, 1000] {
+            for seed in 1..=20 {
+                let report = day3::parse(&generate(3, Some(size), seed).unwrap()).unwrap();
+                assert!(day3::part2(&report).is_ok(), "size {}, seed {}", size, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn packets() {
+        for size in [5, 500, 2000, 20000] {
+            for seed in 1..=3 {
+                let packet = day16::parse(&generate(16, Some(size), seed).unwrap()).unwrap();
+                assert!(day16::part2(&packet).is_ok(), "size {}, seed {}", size, seed);
+            }
+        }
+    }
+}
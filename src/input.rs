@@ -1,18 +1,90 @@
+use crate::error::{self, ensure, OrIo};
 use reqwest::Client;
 use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
 
-pub fn get_input(day: u8) -> String {
+fn download_input(day: u8) -> reqwest::Response {
     let url = std::format!("https://adventofcode.com/2021/day/{}/input", day);
     let url = reqwest::Url::parse(&url).expect("Failed to parse url");
 
     let session = env::var("AOC_SESSION").expect("Please set the AOC_SESSION env variable");
 
     let client = Client::new();
-    let mut res = client
+    client
         .get(url)
         .header("Cookie", std::format!("session={}", session))
         .send()
-        .expect("Request failed");
+        .expect("Request failed")
+}
+
+// AOC_INPUT can point to a local input file (or - for stdin), e.g. a generated one
+pub fn get_input_reader(day: u8) -> Box<dyn BufRead> {
+    match env::var("AOC_INPUT") {
+        Ok(path) if path == "-" => Box::new(io::stdin().lock()),
+        Ok(path) => Box::new(BufReader::new(File::open(path).expect("Opening input file failed"))),
+        Err(_) => Box::new(BufReader::new(download_input(day))),
+    }
+}
+
+pub fn get_input(day: u8) -> String {
+    let mut input = String::new();
+    get_input_reader(day)
+        .read_to_string(&mut input)
+        .expect("Reading input failed");
+    input
+}
+
+// Call f for every non-empty line, reusing a single buffer so that
+// arbitrarily large inputs can be processed in constant memory
+pub fn for_each_line<R: BufRead>(mut reader: R, mut f: impl FnMut(&str) -> error::Result<()>) -> error::Result<()> {
+    let mut line = String::new();
+    let mut number = 1;
+    while reader.read_line(&mut line).or_io("reading the input")? > 0 {
+        let trimmed = line.trim();
+        if !trimmed.is_empty() {
+            error::in_line(f(trimmed), number)?;
+        }
+        line.clear();
+        number += 1;
+    }
+    Ok(())
+}
+
+// Parse every non-empty line, errors are reported with their line number
+pub fn parse_lines<'a, T>(input: &'a str, mut f: impl FnMut(&'a str) -> error::Result<T>) -> error::Result<Vec<T>> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| error::in_line(f(line.trim()), i + 1))
+        .collect()
+}
+
+// A non-empty rectangular grid of single digits, one row per line
+pub fn parse_digit_grid(input: &str) -> error::Result<Vec<Vec<u8>>> {
+    let grid = parse_lines(input, |line| {
+        ensure(line.bytes().all(|b| b.is_ascii_digit()), "expected only digits")?;
+        Ok(line.bytes().map(|b| b - b'0').collect::<Vec<u8>>())
+    })?;
+
+    ensure(!grid.is_empty(), "the grid is empty")?;
+    ensure(grid.iter().all(|row| row.len() == grid[0].len()), "all rows must have the same length")?;
+    Ok(grid)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::Error;
 
-    res.text().expect("Getting request body failed")
+    #[test]
+    fn read_errors() {
+        let mut lines = Vec::new();
+        let result = super::for_each_line(&b"1\n\xff\n2\n"[..], |line| {
+            lines.push(line.to_string());
+            Ok(())
+        });
+        assert!(matches!(result, Err(Error::Io(_))));
+        assert_eq!(lines, ["1"]);
+    }
 }
@@ -1,56 +1,99 @@
-mod day1;
+//! Solutions for Advent of Code 2021.
+//!
+//! Every day is a public module with a `parse` function for the puzzle input
+//! and `part1` / `part2` functions taking the parsed input.
+pub mod day1;
 pub use day1::day1;
 
-mod day2;
+pub mod day2;
 pub use day2::day2;
 
-mod day3;
+pub mod day3;
 pub use day3::day3;
 
-mod day4;
+pub mod day4;
 pub use day4::day4;
 
-mod day5;
+pub mod day5;
 pub use day5::day5;
 
-mod day6;
+pub mod day6;
 pub use day6::day6;
 
-mod day7;
+pub mod day7;
 pub use day7::day7;
 
-mod day8;
+pub mod day8;
 pub use day8::day8;
 
-mod day9;
+pub mod day9;
 pub use day9::day9;
 
-mod day10;
+pub mod day10;
 pub use day10::day10;
 
-mod day11;
+pub mod day11;
 pub use day11::day11;
 
-mod day12;
+pub mod day12;
 pub use day12::day12;
 
-mod day13;
+pub mod day13;
 pub use day13::day13;
 
-mod day14;
+pub mod day14;
 pub use day14::day14;
 
-mod day15;
+pub mod day15;
 pub use day15::day15;
 
-mod day16;
+pub mod day16;
 pub use day16::day16;
 
-mod day17;
+pub mod day17;
 pub use day17::day17;
 
-mod day18;
+pub mod day18;
 pub use day18::day18;
 
+mod error;
+pub use error::{Error, Result};
+
+pub mod log;
+
+pub mod memory;
+
 mod input;
-pub use input::get_input;
+pub use input::{get_input, get_input_reader};
+
+mod animate;
+pub use animate::{animate, frame_time};
+
+mod rng;
+
+pub mod parallel;
+
+pub mod fxhash;
+
+mod generate;
+pub use generate::generate;
+
+pub mod fuzz;
+
+pub mod report;
+pub use report::report;
+
+pub mod algorithms;
+pub use algorithms::verify;
+
+pub mod rig;
+pub use rig::rig;
+
+#[cfg(test)]
+mod crosscheck;
+
+#[cfg(test)]
+mod overflow;
+
+#[cfg(test)]
+mod corpus;
@@ -0,0 +1,57 @@
+//! Minimal leveled logging to stderr, so that the answers on stdout stay clean.
+//!
+//! Solvers report intermediate values with `debug!` (shown with `-v`) and
+//! more detailed, possibly long output with `trace!` (shown with `-vv`).
+//! Every message is prefixed with the day it comes from.
+use std::sync::atomic::{AtomicU8, Ordering};
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Quiet as u8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Quiet = 0,
+    Debug = 1,
+    Trace = 2,
+}
+
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Whether messages of the given level are shown
+pub fn enabled(level: Level) -> bool {
+    LEVEL.load(Ordering::Relaxed) >= level as u8
+}
+
+/// The day a module path belongs to, e.g. day7 for aocmaxnoe2021::day7
+pub fn source(module_path: &str) -> &str {
+    module_path.rsplit("::").next().unwrap_or(module_path)
+}
+
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::Level::Debug) {
+            eprintln!("[{}] {}", $crate::log::source(module_path!()), format_args!($($arg)*));
+        }
+    };
+}
+
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::Level::Trace) {
+            eprintln!("[{}] {}", $crate::log::source(module_path!()), format_args!($($arg)*));
+        }
+    };
+}
+
+pub(crate) use debug;
+pub(crate) use trace;
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn source() {
+        assert_eq!(super::source("aocmaxnoe2021::day7"), "day7");
+        assert_eq!(super::source("day13"), "day13");
+    }
+}
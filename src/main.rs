@@ -1,16 +1,183 @@
+use aocmaxnoe2021::{algorithms, day2, log};
 use std::env;
+use std::path::PathBuf;
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
+fn usage() -> ! {
+    println!("Usage: aocmaxnoe2021 [-v | -vv] <day> [--algo <name>]");
+    println!("       aocmaxnoe2021 verify <day>");
+    println!("       aocmaxnoe2021 animate <day> [--fps <fps>]");
+    println!("       aocmaxnoe2021 generate <day> [--size <size>] [--seed <seed>]");
+    println!("       aocmaxnoe2021 report --out <dir> [--inputs <dir>]");
+    println!("       aocmaxnoe2021 course <csv | replay | compare> [--model <simple | aimed>] [--steps <steps>]");
+    println!("       aocmaxnoe2021 rig <board> [--last]");
+    std::process::exit(1);
+}
+
+fn parse_day(arg: &str) -> u8 {
+    arg.parse().expect("Day must be a number")
+}
 
-    if args.len() != 2 {
-        println!("Usage: aocmaxnoe2021 <day>");
+fn exit_on_error(result: aocmaxnoe2021::Result<()>) {
+    if let Err(err) = result {
+        println!("Error: {}", err);
         std::process::exit(1);
     }
+}
+
+fn animate(args: &[String]) {
+    let mut fps = 10.0;
+
+    match args {
+        [_] => {}
+        [_, flag, value] if flag == "--fps" => {
+            fps = value.parse().expect("fps must be a number");
+        }
+        _ => usage(),
+    }
+
+    let frame_time = aocmaxnoe2021::frame_time(fps).expect("fps must be a positive number");
+    exit_on_error(aocmaxnoe2021::animate(parse_day(&args[0]), frame_time));
+}
+
+fn generate(args: &[String]) {
+    let mut size = None;
+    let mut seed = 1;
+
+    for option in args[1..].chunks(2) {
+        match option {
+            [flag, value] if flag == "--size" => size = Some(value.parse().expect("size must be a number")),
+            [flag, value] if flag == "--seed" => seed = value.parse().expect("seed must be a number"),
+            _ => usage(),
+        }
+    }
+
+    let day = parse_day(&args[0]);
+    match aocmaxnoe2021::generate(day, size, seed) {
+        Some(input) => print!("{}", input),
+        None => {
+            println!("No generator for day {}", day);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn report(args: &[String]) {
+    let mut out = None;
+    let mut inputs = None;
+
+    for option in args.chunks(2) {
+        match option {
+            [flag, value] if flag == "--out" => out = Some(PathBuf::from(value)),
+            [flag, value] if flag == "--inputs" => inputs = Some(PathBuf::from(value)),
+            _ => usage(),
+        }
+    }
+
+    let out = out.unwrap_or_else(|| usage());
+    exit_on_error(aocmaxnoe2021::report(&out, inputs.as_deref()));
+}
+
+// The course of day 2 as csv, up to a step or both models side by side
+fn course(args: &[String]) {
+    let mut model = day2::Model::Aimed;
+    let mut n_steps = None;
+
+    for option in args[1..].chunks(2) {
+        match option {
+            [flag, value] if flag == "--model" && value == "simple" => model = day2::Model::Simple,
+            [flag, value] if flag == "--model" && value == "aimed" => model = day2::Model::Aimed,
+            [flag, value] if flag == "--steps" => n_steps = Some(value.parse().expect("steps must be a number")),
+            _ => usage(),
+        }
+    }
+
+    let view = match args[0].as_str() {
+        "csv" => day2::View::Csv(model),
+        "replay" => day2::View::Replay,
+        "compare" => day2::View::Compare,
+        _ => usage(),
+    };
+    exit_on_error(day2::course(view, n_steps));
+}
+
+// A draw order of day 4 that makes a board win first, or last with --last
+fn rig(args: &[String]) {
+    let board = args[0].parse().expect("board must be a number");
+    let last = match &args[1..] {
+        [] => false,
+        [flag] if flag == "--last" => true,
+        _ => usage(),
+    };
+    exit_on_error(aocmaxnoe2021::rig(board, last));
+}
+
+// -v shows debug output of the solvers on stderr, -vv also the trace output
+fn set_verbosity(args: &mut Vec<String>) {
+    let n_verbose: usize = args
+        .iter()
+        .filter(|arg| arg.len() > 1 && arg.starts_with('-') && arg[1..].bytes().all(|b| b == b'v'))
+        .map(|arg| arg.len() - 1)
+        .sum();
+    args.retain(|arg| !(arg.len() > 1 && arg.starts_with('-') && arg[1..].bytes().all(|b| b == b'v')));
+
+    log::set_level(match n_verbose {
+        0 => log::Level::Quiet,
+        1 => log::Level::Debug,
+        _ => log::Level::Trace,
+    });
+}
 
-    let day: u8 = args[1].parse().expect("Day must be a number");
+fn main() {
+    let mut args: Vec<String> = env::args().collect();
+    set_verbosity(&mut args);
+
+    if args.len() < 2 {
+        usage();
+    }
+
+    if args[1] == "animate" && args.len() > 2 {
+        animate(&args[2..]);
+        return;
+    }
+
+    if args[1] == "generate" && args.len() > 2 {
+        generate(&args[2..]);
+        return;
+    }
 
-    match day {
+    if args[1] == "report" {
+        report(&args[2..]);
+        return;
+    }
+
+    if args[1] == "course" && args.len() > 2 {
+        course(&args[2..]);
+        return;
+    }
+
+    if args[1] == "rig" && args.len() > 2 {
+        rig(&args[2..]);
+        return;
+    }
+
+    if args[1] == "verify" && args.len() == 3 {
+        exit_on_error(aocmaxnoe2021::verify(parse_day(&args[2])));
+        return;
+    }
+
+    match &args[2..] {
+        [] => {}
+        [flag, name] if flag == "--algo" => algorithms::choose(Some(name)),
+        _ => usage(),
+    }
+
+    let day = parse_day(&args[1]);
+    if args.len() > 2 && !algorithms::DAYS.contains(&day) {
+        println!("Day {} has no alternative algorithms", day);
+        std::process::exit(1);
+    }
+
+    let result = match day {
         1 => aocmaxnoe2021::day1(),
         2 => aocmaxnoe2021::day2(),
         3 => aocmaxnoe2021::day3(),
@@ -33,5 +200,7 @@ fn main() {
             println!("Day {} not yet implemented", day);
             std::process::exit(1);
         }
-    }
+    };
+    exit_on_error(result);
+    aocmaxnoe2021::memory::print_summary();
 }
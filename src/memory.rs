@@ -0,0 +1,161 @@
+//! Optional allocation statistics.
+//!
+//! With the `count-allocations` feature a global allocator counts every
+//! allocation, the bytes allocated and the peak of live bytes. Without it,
+//! nothing is counted and [`measure`] returns no stats.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+use std::sync::Mutex;
+
+static N_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// The system allocator, counting allocations on the way
+pub struct CountingAllocator;
+
+fn record_allocation(size: usize) {
+    N_ALLOCATIONS.fetch_add(1, Relaxed);
+    BYTES_ALLOCATED.fetch_add(size, Relaxed);
+    let live = LIVE_BYTES.fetch_add(size, Relaxed) + size;
+    PEAK_LIVE_BYTES.fetch_max(live, Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_allocation(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_allocation(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        LIVE_BYTES.fetch_sub(layout.size(), Relaxed);
+    }
+
+    // a reallocation counts as freeing the old and allocating the new block
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            LIVE_BYTES.fetch_sub(layout.size(), Relaxed);
+            record_allocation(new_size);
+        }
+        new_ptr
+    }
+}
+
+#[cfg(feature = "count-allocations")]
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Allocations made while running a function, the peak is on top of the bytes live before
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Stats {
+    pub count: usize,
+    pub bytes: usize,
+    pub peak: usize,
+}
+
+fn human_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} allocations, {} allocated, {} peak",
+            self.count,
+            human_bytes(self.bytes),
+            human_bytes(self.peak)
+        )
+    }
+}
+
+/// Run f and count its allocations, there are only stats with the count-allocations feature.
+/// The counters are global, so allocations of other threads running at the same time are included.
+pub fn measure<R>(f: impl FnOnce() -> R) -> (R, Option<Stats>) {
+    if !cfg!(feature = "count-allocations") {
+        return (f(), None);
+    }
+
+    let count = N_ALLOCATIONS.load(Relaxed);
+    let bytes = BYTES_ALLOCATED.load(Relaxed);
+    let live = LIVE_BYTES.load(Relaxed);
+    PEAK_LIVE_BYTES.store(live, Relaxed);
+
+    let result = f();
+
+    let stats = Stats {
+        count: N_ALLOCATIONS.load(Relaxed) - count,
+        bytes: BYTES_ALLOCATED.load(Relaxed) - bytes,
+        peak: PEAK_LIVE_BYTES.load(Relaxed).saturating_sub(live),
+    };
+    (result, Some(stats))
+}
+
+static SUMMARY: Mutex<Vec<(&str, Stats)>> = Mutex::new(Vec::new());
+
+/// Measure f and keep its stats under the given label for the summary
+pub fn record<R>(label: &'static str, f: impl FnOnce() -> R) -> R {
+    let (result, stats) = measure(f);
+    if let Some(stats) = stats {
+        SUMMARY.lock().unwrap().push((label, stats));
+    }
+    result
+}
+
+/// Print and forget the recorded stats on stderr, next to the answers on stdout.
+/// Prints nothing without the count-allocations feature.
+pub fn print_summary() {
+    for (label, stats) in SUMMARY.lock().unwrap().drain(..) {
+        eprintln!("Memory {}: {}", label, stats);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn measure() {
+        let (v, stats) = super::measure(|| vec![0u8; 4096]);
+        assert_eq!(v.len(), 4096);
+
+        if cfg!(feature = "count-allocations") {
+            let stats = stats.unwrap();
+            assert!(stats.count >= 1);
+            assert!(stats.bytes >= 4096 && stats.peak >= 4096);
+        } else {
+            assert!(stats.is_none());
+        }
+    }
+
+    #[test]
+    fn human_bytes() {
+        assert_eq!(super::human_bytes(1000), "1000 B");
+        assert_eq!(super::human_bytes(1536), "1.5 KiB");
+        assert_eq!(super::human_bytes(3 * 1024 * 1024), "3.0 MiB");
+    }
+}
@@ -0,0 +1,90 @@
+// Inputs that overflowed the integer types the solutions used to use,
+// they now either give the right answer or report the overflow
+use crate::error::Error;
+use crate::{day1, day10, day14, day16, day17, day2, day6, day7, day8, day9};
+use itertools::Itertools;
+
+#[test]
+fn day1_window_sums() {
+    let max = i32::MAX;
+    let input = format!("{}\n{}\n{}\n{}\n", max - 1, max, max, max);
+    let numbers = day1::parse(&input).unwrap();
+
+    assert_eq!(day1::part2(&numbers), 1);
+    assert_eq!(day1::solve_reader(input.as_bytes()), Ok((1, 1)));
+}
+
+#[test]
+fn day2_large_course() {
+    let input = "forward 2000000000\ndown 2000000000\n".repeat(3);
+    let commands = day2::parse(&input).unwrap();
+
+    assert_eq!(day2::part1(&commands), Ok(36_000_000_000_000_000_000));
+    assert_eq!(day2::part2(&commands), Err(Error::Overflow));
+}
+
+#[test]
+fn day6_many_days() {
+    let fishes = day6::parse("3,4,3,1,2").unwrap();
+
+    assert_eq!(day6::simulate_fishes(&fishes, 256), Ok(26984457539));
+    assert!(day6::simulate_fishes(&fishes, 600).unwrap() > u64::MAX as u128);
+    assert_eq!(day6::simulate_fishes(&fishes, 2000), Err(Error::Overflow));
+}
+
+#[test]
+fn day7_far_crabs() {
+    let positions = day7::parse("0,3000000000").unwrap();
+
+    assert_eq!(day7::part1(&positions), Ok(3_000_000_000));
+    assert_eq!(day7::part2(&positions), Ok(2 * 1_500_000_000 * 1_500_000_001 / 2));
+}
+
+#[test]
+fn day8_many_displays() {
+    let line = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf\n";
+    let displays = day8::parse(&line.repeat(500_000)).unwrap();
+
+    assert_eq!(day8::part2(&displays), Ok(5353 * 500_000));
+}
+
+#[test]
+fn day9_product() {
+    let grid = day9::parse("0\n").unwrap();
+    assert_eq!(day9::part2(&grid), Ok(1));
+}
+
+#[test]
+fn day10_long_lines() {
+    let input = "<".repeat(40);
+    assert_eq!(day10::part2(&day10::parse(&input).unwrap()), Ok(4 * (5u128.pow(40) - 1) / 4));
+
+    let input = "<".repeat(60);
+    assert_eq!(day10::part2(&day10::parse(&input).unwrap()), Err(Error::Overflow));
+    assert_eq!(day10::solve_reader(input.as_bytes()).unwrap().1, Err(Error::Overflow));
+}
+
+#[test]
+fn day14_many_steps() {
+    let rules = ["A", "B"].iter().cartesian_product(["A", "B"]).map(|(a, b)| format!("{}{} -> A", a, b)).join("\n");
+    let input = format!("AB\n\n{}\n", rules);
+    let polymer = day14::parse(&input).unwrap();
+
+    // the polymer grows to 2^n + 1 elements, only the last one is a B
+    assert_eq!(day14::polymer_development(&polymer, 70), Ok((1 << 70) - 1));
+    assert_eq!(day14::polymer_development(&polymer, 130), Err(Error::Overflow));
+}
+
+#[test]
+fn day16_big_values() {
+    // product of the literals 2^32 and 2^32
+    let packet = day16::parse("0600848C2108421000918421084200").unwrap();
+    assert_eq!(day16::part2(&packet), Ok(1 << 64));
+}
+
+#[test]
+fn day17_deep_target() {
+    let target_area = day17::parse("target area: x=10..10, y=-70000..-69999").unwrap();
+    assert!(target_area.hits(4, 69999));
+    assert_eq!(day17::max_height(69999), 69999 * 70000 / 2);
+}
@@ -0,0 +1,65 @@
+//! Helpers for solutions that split independent work over scoped threads
+use std::env;
+use std::thread;
+
+/// Number of threads used by the parallel solutions,
+/// the AOC_THREADS env variable overrides the available parallelism
+pub fn n_threads() -> usize {
+    match env::var("AOC_THREADS") {
+        Ok(n) => n.parse::<usize>().expect("AOC_THREADS must be a number").max(1),
+        Err(_) => thread::available_parallelism().map_or(1, |n| n.get()),
+    }
+}
+
+/// Apply f to every item using up to n_threads threads, results are in the order of the items.
+/// Thread t handles the items t, t + n_threads, ..., which spreads expensive neighboring items.
+pub fn map<T: Sync, R: Send>(items: &[T], n_threads: usize, f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let n_threads = n_threads.clamp(1, items.len().max(1));
+    if n_threads == 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let f = &f;
+    let per_thread: Vec<Vec<R>> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..n_threads)
+            .map(|t| scope.spawn(move || items.iter().skip(t).step_by(n_threads).map(f).collect::<Vec<R>>()))
+            .collect();
+
+        handles.into_iter().map(|h| h.join().expect("Worker thread panicked")).collect()
+    });
+
+    let mut per_thread: Vec<_> = per_thread.into_iter().map(Vec::into_iter).collect();
+    (0..items.len()).map(|i| per_thread[i % n_threads].next().unwrap()).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::generate::generate;
+    use crate::{day12, day17, day18};
+
+    #[test]
+    fn map_keeps_order() {
+        let items: Vec<usize> = (0..103).collect();
+        for n_threads in [1, 2, 7, 200] {
+            assert_eq!(super::map(&items, n_threads, |i| i * 2), items.iter().map(|i| i * 2).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn same_answers() {
+        for seed in 1..=5 {
+            let input = generate(12, Some(10), seed).unwrap();
+            let caves = day12::parse(&input).unwrap();
+            assert_eq!(day12::count_paths(&caves, true, 1), day12::count_paths(&caves, true, 4));
+            assert_eq!(day12::count_paths(&caves, false, 1), day12::count_paths(&caves, false, 4));
+
+            let input = generate(17, Some(30), seed).unwrap();
+            let target_area = day17::parse(&input).unwrap();
+            assert_eq!(target_area.possible_shots_with(1), target_area.possible_shots_with(4));
+
+            let input = generate(18, Some(10), seed).unwrap();
+            let numbers = day18::parse(&input).unwrap();
+            assert_eq!(day18::largest_magnitude(&numbers, 1), day18::largest_magnitude(&numbers, 4));
+        }
+    }
+}
@@ -0,0 +1,376 @@
+//! A static HTML report of all days.
+//!
+//! The page contains the answers, the time spent parsing and in each part and
+//! images of some grids. It is self-contained and works offline, the only links
+//! go to the puzzle descriptions. The inputs themselves are not included.
+use crate::error::{self, OrIo};
+use crate::input;
+use crate::{day1, day10, day11, day12, day13, day14, day15, day16, day17, day18};
+use crate::{day2, day3, day4, day5, day6, day7, day8, day9};
+use crate::fxhash::FxBuildHasher;
+use itertools::Itertools;
+use std::fmt::Write;
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// The days with a solution
+pub const DAYS: RangeInclusive<u8> = 1..=18;
+
+// Size of a grid cell in the images, in pixels
+const CELL_SIZE: usize = 4;
+
+/// Time spent in each step of a solution
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Timings {
+    pub parse: Duration,
+    pub part1: Duration,
+    pub part2: Duration,
+}
+
+/// The answers of a day with their timings and images of the puzzle state
+#[derive(Debug, Default)]
+pub struct Solution {
+    pub part1: String,
+    pub part2: String,
+    pub timings: Timings,
+    /// Caption and svg of every image
+    pub images: Vec<(String, String)>,
+}
+
+// Answers of all types become text
+pub(crate) trait Answer: Sized {
+    fn into_result(self) -> error::Result<String>;
+
+    // Errors included
+    fn text(self) -> String {
+        error::answer(self.into_result())
+    }
+}
+
+macro_rules! display_answer {
+    ($($t:ty),*) => {
+        $(impl Answer for $t {
+            fn into_result(self) -> error::Result<String> {
+                Ok(self.to_string())
+            }
+        })*
+    };
+}
+
+display_answer!(u32, u64, u128, i64, i128, usize, String, day3::WideNumber);
+
+impl<T: Answer> Answer for error::Result<T> {
+    fn into_result(self) -> error::Result<String> {
+        self.and_then(Answer::into_result)
+    }
+}
+
+fn timed<R>(f: impl FnOnce() -> R) -> (R, Duration) {
+    let t0 = Instant::now();
+    let result = f();
+    (result, t0.elapsed())
+}
+
+// Parse the input and solve both parts of a day, evaluates to the parsed input and the solution
+macro_rules! run_day {
+    ($day:ident, $input:expr) => {{
+        let (parsed, parse) = timed(|| $day::parse($input));
+        let parsed = parsed?;
+        let (part1, part1_time) = timed(|| $day::part1(&parsed).text());
+        let (part2, part2_time) = timed(|| $day::part2(&parsed).text());
+        let timings = Timings { parse, part1: part1_time, part2: part2_time };
+        (parsed, Solution { part1, part2, timings, images: Vec::new() })
+    }};
+}
+
+/// Solve both parts of a day and draw its images
+pub fn solve(day: u8, input: &str) -> error::Result<Solution> {
+    let solution = match day {
+        1 => run_day!(day1, input).1,
+        2 => run_day!(day2, input).1,
+        3 => run_day!(day3, input).1,
+        4 => run_day!(day4, input).1,
+        5 => run_day!(day5, input).1,
+        6 => run_day!(day6, input).1,
+        7 => run_day!(day7, input).1,
+        8 => run_day!(day8, input).1,
+        9 => {
+            let (grid, mut solution) = run_day!(day9, input);
+            solution.images.push(("Basins".to_string(), basins_svg(&grid)));
+            solution
+        }
+        10 => run_day!(day10, input).1,
+        11 => run_day!(day11, input).1,
+        12 => run_day!(day12, input).1,
+        13 => {
+            let (manual, mut solution) = run_day!(day13, input);
+            solution.images.push(("Folded paper".to_string(), dots_svg(&manual)));
+            solution
+        }
+        14 => run_day!(day14, input).1,
+        15 => {
+            let (risk, mut solution) = run_day!(day15, input);
+            solution.images.push(("Lowest risk path".to_string(), path_svg(&risk)));
+            solution
+        }
+        16 => run_day!(day16, input).1,
+        17 => run_day!(day17, input).1,
+        18 => run_day!(day18, input).1,
+        _ => return Err(error::Error::Parse(format!("no solution for day {}", day))),
+    };
+    Ok(solution)
+}
+
+// Parse the input and solve one part of a day
+macro_rules! run_part {
+    ($day:ident, $part:expr, $input:expr) => {{
+        let parsed = $day::parse($input)?;
+        match $part {
+            1 => $day::part1(&parsed).into_result(),
+            2 => $day::part2(&parsed).into_result(),
+            _ => Err(error::Error::Parse(format!("no part {}", $part))),
+        }
+    }};
+}
+
+/// The answer to one part of a day
+pub fn solve_part(day: u8, part: u8, input: &str) -> error::Result<String> {
+    match day {
+        1 => run_part!(day1, part, input),
+        2 => run_part!(day2, part, input),
+        3 => run_part!(day3, part, input),
+        4 => run_part!(day4, part, input),
+        5 => run_part!(day5, part, input),
+        6 => run_part!(day6, part, input),
+        7 => run_part!(day7, part, input),
+        8 => run_part!(day8, part, input),
+        9 => run_part!(day9, part, input),
+        10 => run_part!(day10, part, input),
+        11 => run_part!(day11, part, input),
+        12 => run_part!(day12, part, input),
+        13 => run_part!(day13, part, input),
+        14 => run_part!(day14, part, input),
+        15 => run_part!(day15, part, input),
+        16 => run_part!(day16, part, input),
+        17 => run_part!(day17, part, input),
+        18 => run_part!(day18, part, input),
+        _ => Err(error::Error::Parse(format!("no solution for day {}", day))),
+    }
+}
+
+// One rect per horizontal run of cells with the same color, cells without a color stay empty
+fn svg_grid(n_rows: usize, n_cols: usize, background: &str, color: impl Fn(usize, usize) -> Option<String>) -> String {
+    let (width, height) = (n_cols * CELL_SIZE, n_rows * CELL_SIZE);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" shape-rendering=\"crispEdges\">\
+         <rect width=\"{0}\" height=\"{1}\" fill=\"{2}\"/>",
+        width, height, background
+    );
+
+    for row in 0..n_rows {
+        let runs = (0..n_cols).map(|col| color(row, col)).enumerate().group_by(|(_, color)| color.clone());
+        for (color, run) in &runs {
+            if let Some(color) = color {
+                let cols: Vec<usize> = run.map(|(col, _)| col).collect();
+                write!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                    cols[0] * CELL_SIZE,
+                    row * CELL_SIZE,
+                    cols.len() * CELL_SIZE,
+                    CELL_SIZE,
+                    color
+                )
+                .unwrap();
+            }
+        }
+    }
+    svg + "</svg>"
+}
+
+// Every basin gets its own hue, the ridges of height 9 stay dark
+fn basins_svg(grid: &[Vec<u8>]) -> String {
+    let basins = day9::basins(grid);
+    svg_grid(grid.len(), grid[0].len(), "#333", |row, col| {
+        basins[row][col].map(|basin| format!("hsl({},65%,60%)", basin * 137 % 360))
+    })
+}
+
+fn dots_svg(manual: &day13::Input) -> String {
+    let grid = day13::fold_all::<FxBuildHasher>(manual);
+    let n_cols = grid.iter().map(|(x, _)| *x as usize).max().unwrap_or(0) + 1;
+    let n_rows = grid.iter().map(|(_, y)| *y as usize).max().unwrap_or(0) + 1;
+    svg_grid(n_rows, n_cols, "#0f0f23", |row, col| {
+        grid.contains(&(col as u32, row as u32)).then(|| "#ffff66".to_string())
+    })
+}
+
+// Only the path is drawn, so the image does not give away the risk levels of the input
+fn path_svg(risk: &day15::Input) -> String {
+    let mut on_path = vec![vec![false; risk[0].len()]; risk.len()];
+    for (row, col) in day15::lowest_risk_path(risk, 1) {
+        on_path[row][col] = true;
+    }
+    svg_grid(risk.len(), risk[0].len(), "#0f0f23", |row, col| on_path[row][col].then(|| "#e33".to_string()))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn answer_html(answer: &str) -> String {
+    if answer.contains('\n') {
+        format!("<pre>{}</pre>", escape(answer))
+    } else {
+        escape(answer)
+    }
+}
+
+fn micros(duration: Duration) -> String {
+    format!("{} µs", duration.as_micros())
+}
+
+const STYLE: &str = "body { font-family: monospace; background: #0f0f23; color: #ccc; margin: 2em; }
+a { color: #009900; }
+table { border-collapse: collapse; }
+th, td { padding: 0.3em 0.8em; border-bottom: 1px solid #333; text-align: right; vertical-align: top; }
+pre { margin: 0; line-height: 1; text-align: left; }
+figure { display: inline-block; margin: 1em 2em 1em 0; }
+svg { max-width: 100%; height: auto; }
+.error { color: #e33; }";
+
+/// The html page for the solutions of the given days
+pub fn html(solutions: &[(u8, error::Result<Solution>)]) -> String {
+    let mut rows = String::new();
+    let mut images = String::new();
+    let mut total = Duration::ZERO;
+
+    for (day, solution) in solutions {
+        let link = format!("<a href=\"https://adventofcode.com/2021/day/{0}\">Day {0}</a>", day);
+        match solution {
+            Ok(solution) => {
+                let t = &solution.timings;
+                total += t.parse + t.part1 + t.part2;
+                writeln!(
+                    rows,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    link,
+                    answer_html(&solution.part1),
+                    answer_html(&solution.part2),
+                    micros(t.parse),
+                    micros(t.part1),
+                    micros(t.part2),
+                    micros(t.parse + t.part1 + t.part2),
+                )
+                .unwrap();
+
+                for (caption, svg) in &solution.images {
+                    writeln!(images, "<figure>{}<figcaption>Day {}: {}</figcaption></figure>", svg, day, escape(caption)).unwrap();
+                }
+            }
+            Err(err) => {
+                writeln!(rows, "<tr><td>{}</td><td class=\"error\" colspan=\"6\">Error: {}</td></tr>", link, escape(&err.to_string())).unwrap();
+            }
+        }
+    }
+
+    format!(
+        "<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"utf-8\">
+<title>Advent of Code 2021</title>
+<style>
+{}
+</style>
+</head>
+<body>
+<h1>Advent of Code 2021</h1>
+<table>
+<tr><th>Day</th><th>Part 1</th><th>Part 2</th><th>Parse</th><th>Part 1</th><th>Part 2</th><th>Total</th></tr>
+{}<tr><th colspan=\"6\">Total</th><td>{}</td></tr>
+</table>
+{}</body>
+</html>
+",
+        STYLE,
+        rows,
+        micros(total),
+        images
+    )
+}
+
+/// Solve all days and write the report to out/index.html. The inputs are read from
+/// inputs/dayN.txt if given, days without an input file are left out, else they are downloaded.
+pub fn report(out: &Path, inputs: Option<&Path>) -> error::Result<()> {
+    let mut solutions = Vec::new();
+
+    for day in DAYS {
+        let input = match inputs {
+            Some(dir) => match fs::read_to_string(dir.join(format!("day{}.txt", day))) {
+                Ok(input) => input,
+                Err(_) => continue,
+            },
+            None => input::get_input(day),
+        };
+        solutions.push((day, solve(day, &input)));
+    }
+
+    fs::create_dir_all(out).or_io(&format!("creating {}", out.display()))?;
+    let path = out.join("index.html");
+    fs::write(&path, html(&solutions)).or_io(&format!("writing {}", path.display()))?;
+    println!("Wrote report for {} days to {}", solutions.len(), path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::day15;
+    use crate::error::Error;
+    use crate::generate::generate;
+    use std::fs;
+
+    #[test]
+    fn write_errors() {
+        // a directory can not be created below a file
+        let file = std::env::temp_dir().join(format!("aoc2021-report-{}", std::process::id()));
+        fs::write(&file, "").unwrap();
+        let result = super::report(&file.join("out"), Some(&file));
+        fs::remove_file(&file).unwrap();
+        assert!(matches!(result, Err(Error::Io(_))), "{:?}", result);
+    }
+
+    #[test]
+    fn page() {
+        let solutions: Vec<_> = super::DAYS
+            .map(|day| (day, super::solve(day, &generate(day, None, 1).unwrap())))
+            .chain(std::iter::once((19, super::solve(19, ""))))
+            .collect();
+
+        for day in [9, 13, 15] {
+            assert_eq!(solutions[day - 1].1.as_ref().unwrap().images.len(), 1);
+        }
+
+        let page = super::html(&solutions);
+        assert!(page.contains("https://adventofcode.com/2021/day/18"));
+        assert_eq!(page.matches("<svg").count(), 3);
+        assert!(page.contains("Error: Invalid input, no solution for day 19"));
+        assert_eq!(super::escape("<b>\"&\""), "&lt;b&gt;&quot;&amp;&quot;");
+        // works offline, nothing but the puzzle links leaves the page
+        assert!(!page.contains("<script") && !page.contains("src="));
+    }
+
+    #[test]
+    fn lowest_risk_path() {
+        for seed in 1..=5 {
+            let risk = day15::parse(&generate(15, Some(30), seed).unwrap()).unwrap();
+            let path = day15::lowest_risk_path(&risk, 1);
+            assert_eq!(path.first(), Some(&(0, 0)));
+            assert_eq!(path.last(), Some(&(29, 29)));
+            let total: u64 = path[1..].iter().map(|(r, c)| risk[*r][*c] as u64).sum();
+            assert_eq!(total, day15::part1(&risk));
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! Rigged bingo: draw orders that make a chosen board of day 4 win first or last.
+//!
+//! Every draw order is checked by playing it with `day4::simulate`.
+use crate::day4::{self, Game};
+use crate::error::{self, ensure};
+use crate::input;
+
+/// The numbers of every winning line of every board
+fn number_lines(game: &Game) -> Vec<Vec<Vec<u32>>> {
+    game.boards
+        .iter()
+        .map(|board| {
+            board
+                .lines(&game.patterns)
+                .into_iter()
+                .map(|line| line.into_iter().map(|cell| board.numbers[cell]).collect())
+                .collect()
+        })
+        .collect()
+}
+
+/// Whether the target board wins first and with the last number of the draws
+fn wins_first(game: &Game, target: usize, draws: &[u32]) -> bool {
+    let game = Game{numbers: draws.to_vec(), boards: game.boards.clone(), patterns: game.patterns.clone()};
+    let timeline = day4::simulate(&game);
+    timeline.first().map(|win| (win.board, win.turn)) == Some((target, draws.len() - 1))
+}
+
+/// Whether all boards win and the target board is the last one, with the last number of the draws
+fn wins_last(game: &Game, target: usize, draws: &[u32]) -> bool {
+    let game = Game{numbers: draws.to_vec(), boards: game.boards.clone(), patterns: game.patterns.clone()};
+    let timeline = day4::simulate(&game);
+    timeline.never_won.is_empty() && timeline.last().map(|win| (win.board, win.turn)) == Some((target, draws.len() - 1))
+}
+
+fn check_target(game: &Game, target: usize) -> error::Result<()> {
+    ensure(target < game.boards.len(), "there is no such board")
+}
+
+/// The shortest draw order that makes the target board win first, None if it never can.
+///
+/// More numbers can only complete more boards, so the shortest order draws a
+/// single line of the target, with a number that completes no other board first last.
+pub fn win_first(game: &Game, target: usize) -> error::Result<Option<Vec<u32>>> {
+    check_target(game, target)?;
+    let mut lines = number_lines(game).swap_remove(target);
+    lines.sort_by_key(Vec::len);
+
+    for line in lines {
+        for last in 0..line.len() {
+            let mut draws = line.clone();
+            let number = draws.remove(last);
+            draws.push(number);
+            if wins_first(game, target, &draws) {
+                return Ok(Some(draws));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Numbers of the target kept back until the end, at least one of every line of the target
+struct Search<'a> {
+    game: &'a Game,
+    target: usize,
+    lines: Vec<Vec<Vec<u32>>>,
+    blocked: Vec<u32>,
+}
+
+impl Search<'_> {
+    // Boards before the target may complete with the same number as the target,
+    // all others need a line without any of the blocked numbers
+    fn allowed_in_line(&self, board: usize) -> usize {
+        if board < self.target {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn feasible(&self) -> bool {
+        self.lines.iter().enumerate().filter(|(board, _)| *board != self.target).all(|(board, lines)| {
+            lines.iter().any(|line| line.iter().filter(|n| self.blocked.contains(n)).count() <= self.allowed_in_line(board))
+        })
+    }
+
+    /// Draw all other numbers of the target and a line of every other board avoiding
+    /// the blocked numbers, except the last one which completes the target
+    fn draws(&self, last: u32) -> Option<Vec<u32>> {
+        let is_free = |n: &u32| *n == last || !self.blocked.contains(n);
+        let mut draws: Vec<u32> = self.lines[self.target].iter().flatten().copied().filter(|n| !self.blocked.contains(n)).collect();
+
+        for (board, lines) in self.lines.iter().enumerate().filter(|(board, _)| *board != self.target) {
+            let line = if board < self.target {
+                lines.iter().find(|line| line.iter().all(is_free))?
+            } else {
+                lines.iter().find(|line| line.iter().all(|n| !self.blocked.contains(n)))?
+            };
+            draws.extend(line.iter().filter(|n| **n != last));
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        draws.retain(|n| seen.insert(*n));
+        draws.push(last);
+        Some(draws)
+    }
+
+    // Block a number of the first target line without one, until all lines have one
+    fn search(&mut self) -> Option<Vec<u32>> {
+        if !self.feasible() {
+            return None;
+        }
+
+        let open = self.lines[self.target].iter().find(|line| line.iter().all(|n| !self.blocked.contains(n))).cloned();
+        let line = match open {
+            Some(line) => line,
+            None => {
+                return self
+                    .blocked
+                    .iter()
+                    .filter_map(|last| self.draws(*last))
+                    .find(|draws| wins_last(self.game, self.target, draws))
+            }
+        };
+
+        for number in line {
+            self.blocked.push(number);
+            let draws = self.search();
+            self.blocked.pop();
+            if draws.is_some() {
+                return draws;
+            }
+        }
+        None
+    }
+}
+
+/// A draw order in which every board wins and the target board wins last, None if there is none.
+///
+/// The target must not complete before all other boards, so some of its numbers are held
+/// back until the end, at least one of each of its lines. All sets of held back numbers
+/// are searched, the minimal ones among them decide whether the target can win last.
+pub fn win_last(game: &Game, target: usize) -> error::Result<Option<Vec<u32>>> {
+    check_target(game, target)?;
+    let mut search = Search{game, target, lines: number_lines(game), blocked: Vec::new()};
+    Ok(search.search())
+}
+
+/// Print a draw order for the day 4 input that makes board k, counting from 1, win first or last
+pub fn rig(board: usize, last: bool) -> error::Result<()> {
+    let game = day4::parse(&input::get_input(4))?;
+    ensure(board > 0, "boards are counted from 1")?;
+
+    let (draws, goal) = if last {
+        (win_last(&game, board - 1)?, "last")
+    } else {
+        (win_first(&game, board - 1)?, "first")
+    };
+
+    match draws {
+        Some(draws) => {
+            println!("Board {} wins {} after {} numbers:", board, goal, draws.len());
+            println!("{}", draws.iter().map(u32::to_string).collect::<Vec<_>>().join(","));
+        }
+        None => println!("Board {} can never win {}", board, goal),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::day4::{self, Pattern};
+
+    const BOARDS: &str = "1
+
+1 2 3
+4 5 6
+7 8 9
+
+9 8 7
+6 5 4
+3 2 1
+
+5 10 11
+12 13 14
+15 16 17
+";
+
+    #[test]
+    fn first() {
+        let game = day4::parse(BOARDS).unwrap();
+        // a row is enough for board 3, which shares only the 5
+        assert_eq!(super::win_first(&game, 2).unwrap().map(|draws| draws.len()), Some(3));
+
+        // boards 1 and 2 share all numbers and complete together, the first of them wins
+        let draws = super::win_first(&game, 0).unwrap().unwrap();
+        assert_eq!(draws.len(), 3);
+        assert_eq!(day4::simulate(&day4::Game{numbers: draws, ..game}).first().unwrap().board, 0);
+        let game = day4::parse(BOARDS).unwrap();
+        assert_eq!(super::win_first(&game, 1).unwrap(), None);
+        assert!(super::win_first(&game, 3).is_err());
+    }
+
+    #[test]
+    fn last() {
+        let mut game = day4::parse(BOARDS).unwrap();
+        for target in 0..3 {
+            let draws = super::win_last(&game, target).unwrap();
+            if target == 0 {
+                // board 2 always completes together with board 1 and wins after it
+                assert_eq!(draws, None);
+                continue;
+            }
+            let timeline = day4::simulate(&day4::Game{numbers: draws.unwrap(), boards: game.boards.clone(), patterns: game.patterns.clone()});
+            assert_eq!(timeline.wins.len(), 3);
+            assert_eq!(timeline.last().unwrap().board, target);
+        }
+
+        // with the full card, board 3 completes only after its 5 is drawn
+        game.patterns = vec![Pattern::FullCard];
+        assert!(super::win_last(&game, 2).unwrap().is_some());
+        assert_eq!(super::win_last(&game, 0).unwrap(), None);
+    }
+}